@@ -0,0 +1,223 @@
+use std::fs;
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::Result;
+use async_std::task;
+
+use bellbird_core::directories::Directories;
+use bellbird_core::notes::NOTES_EXTENSION;
+use bellbird_core::search::Search;
+use bellbird_core::vault::Vault;
+
+const SEARCH_LIMIT: usize = 20;
+const DEFAULT_EDITOR: &str = "vi";
+
+// every command works on the app's vault, so the app sees pins
+// and moves right away
+#[derive(Debug)]
+pub struct Commands;
+
+impl Commands {
+	// folders first, pinned entries are marked with a `*`
+	pub fn ls(folder: Option<&String>) -> Result<()> {
+		let vault = Vault::current();
+		let path = match folder {
+			Some(folder) => resolve(folder),
+			None => match Directories::current_directory_path() {
+				Some(path) if path.is_dir() => path,
+				_ => vault.root().to_path_buf()
+			}
+		};
+		if !path.is_dir() {
+			return Err(anyhow::anyhow!("No such folder: {}", path.display()))
+		}
+
+		for directory in vault.directories(&path, 1).unwrap_or_default() {
+			println!("{}{}/", pin_marker(vault.is_pinned(&directory.path)), directory.name);
+		}
+		for note in task::block_on(vault.notes(&path))? {
+			println!("{}{}", pin_marker(note.is_pinned), note.name);
+		}
+		Ok(())
+	}
+
+	// the text is taken from the arguments or, when piped in, from stdin
+	pub fn create(note: &str, text: &[String]) -> Result<()> {
+		let vault = Vault::current();
+		let path = note_path(note);
+		if !path.parent().is_some_and(|parent| parent.is_dir()) {
+			return Err(anyhow::anyhow!("No such folder: {}", parent_display(&path)))
+		}
+
+		let content = match text.is_empty() {
+			false => text.join(" "),
+			true if !std::io::stdin().is_terminal() => {
+				let mut content = String::new();
+				std::io::stdin().read_to_string(&mut content)?;
+				content
+			},
+			true => String::new()
+		};
+
+		let path = vault.create_note(&path)?;
+		if !content.is_empty() {
+			vault.write_note(&path, &content)?;
+		}
+		println!("{}", relative_display(&path));
+		Ok(())
+	}
+
+	pub fn cat(note: &str) -> Result<()> {
+		let path = existing_path(note)?;
+		if path.is_dir() {
+			return Err(anyhow::anyhow!("´{note}´ is a folder"))
+		}
+		print!("{}", fs::read_to_string(&path)?);
+		Ok(())
+	}
+
+	// notes that don't exist yet are created first
+	pub fn edit(note: &str) -> Result<()> {
+		let vault = Vault::current();
+		let mut path = note_path(note);
+		if path.is_dir() {
+			return Err(anyhow::anyhow!("´{note}´ is a folder"))
+		}
+		if !path.exists() {
+			path = vault.create_note(&path)?;
+		}
+
+		let editor = std::env::var("EDITOR")
+			.ok()
+			.filter(|editor| !editor.trim().is_empty())
+			.unwrap_or(DEFAULT_EDITOR.to_string());
+		// `$EDITOR` may come with arguments, e.g. `code --wait`
+		let mut editor_args = editor.split_whitespace();
+		let program = editor_args.next().unwrap_or(DEFAULT_EDITOR);
+		let status = Command::new(program)
+			.args(editor_args)
+			.arg(&path)
+			.status()
+			.map_err(|e| anyhow::anyhow!("Could not start ´{editor}´: {e}"))?;
+		if !status.success() {
+			return Err(anyhow::anyhow!("´{editor}´ exited with {status}"))
+		}
+
+		// the edit ends up in the note's history like one made in the app
+		let content = fs::read_to_string(&path)?;
+		vault.snapshot(&path, &content)?;
+		Ok(())
+	}
+
+	// renames, or moves into `new_path` if that is an existing folder
+	pub fn mv(path: &str, new_path: &str) -> Result<()> {
+		let vault = Vault::current();
+		let path = existing_path(path)?;
+		let mut new_path = resolve(new_path);
+		let is_move = new_path.is_dir();
+		if !is_move && !path.is_dir() {
+			new_path = with_note_extension(new_path);
+		}
+		if !is_move && new_path.exists() {
+			return Err(anyhow::anyhow!("´{}´ already exists", relative_display(&new_path)))
+		}
+		if !is_move && !new_path.parent().is_some_and(|parent| parent.is_dir()) {
+			return Err(anyhow::anyhow!("No such folder: {}", parent_display(&new_path)))
+		}
+
+		let new_path = match (path.is_dir(), is_move) {
+			(true, true) => vault.move_directory(&path, &new_path)?,
+			(true, false) => {
+				vault.rename_directory(&path, &new_path)?;
+				new_path
+			},
+			(false, true) => task::block_on(vault.move_note(&path, &new_path))?,
+			(false, false) => {
+				task::block_on(vault.rename_note(&path, &new_path))?;
+				new_path
+			}
+		};
+		println!("{}", relative_display(&new_path));
+		Ok(())
+	}
+
+	// folders go to the trash with everything inside them
+	pub fn rm(path: &str) -> Result<()> {
+		let vault = Vault::current();
+		let path = existing_path(path)?;
+		match path.is_dir() {
+			true => vault.delete_directory(&path, true)?,
+			false => {
+				vault.delete_note(&path)?;
+			}
+		}
+		Ok(())
+	}
+
+	pub fn set_is_pinned(path: &str, is_pinned: bool) -> Result<()> {
+		let path = existing_path(path)?;
+		Vault::current().set_is_pinned(&path, is_pinned)
+	}
+
+	// best matches first, each with the lines that matched
+	pub fn search(query: &str) -> Result<()> {
+		for result in Search::query(query, SEARCH_LIMIT) {
+			println!("{}", relative_display(&result.path));
+			for snippet in result.snippets {
+				println!("    {}", snippet.replace('\n', " "));
+			}
+		}
+		Ok(())
+	}
+}
+
+// relative paths start at the notes directory
+fn resolve(path: &str) -> PathBuf {
+	Vault::current().root().join(path)
+}
+
+fn note_path(note: &str) -> PathBuf {
+	let path = resolve(note);
+	match path.is_dir() {
+		true => path,
+		false => with_note_extension(path)
+	}
+}
+
+// a folder or a note that has to exist
+fn existing_path(path: &str) -> Result<PathBuf> {
+	let resolved_path = note_path(path);
+	match resolved_path.exists() {
+		true => Ok(resolved_path),
+		false => Err(anyhow::anyhow!("No such note or folder: {path}"))
+	}
+}
+
+fn with_note_extension(path: PathBuf) -> PathBuf {
+	if path.extension().is_some_and(|extension| extension == NOTES_EXTENSION) {
+		return path
+	}
+	let mut path = path.into_os_string();
+	path.push(format!(".{NOTES_EXTENSION}"));
+	PathBuf::from(path)
+}
+
+fn relative_display(path: &Path) -> String {
+	path.strip_prefix(Vault::current().root())
+		.unwrap_or(path)
+		.display()
+		.to_string()
+}
+
+fn parent_display(path: &Path) -> String {
+	path.parent().map(relative_display).unwrap_or_default()
+}
+
+fn pin_marker(is_pinned: bool) -> &'static str {
+	match is_pinned {
+		true => "* ",
+		false => "  "
+	}
+}
@@ -0,0 +1,61 @@
+use std::process::ExitCode;
+
+use crate::commands::Commands;
+
+mod commands;
+
+const USAGE: &str = "\
+Usage: bellbird <command> [arguments]
+
+Paths are relative to the notes directory, the `.note` extension is optional.
+
+Commands:
+  ls [folder]             list the folders and notes of a folder,
+                          the folder open in the app by default
+  new <note> [text...]    create a note, the text is read from stdin
+                          when it is piped in
+  cat <note>              print a note
+  edit <note>             open a note in $EDITOR, missing notes
+                          are created first
+  mv <path> <new path>    rename a note or folder, or move it into a folder
+  rm <path>               move a note or folder to the trash
+  pin <path>              pin a note or folder
+  unpin <path>            unpin a note or folder
+  search <query...>       search all notes";
+
+fn main() -> ExitCode {
+	let args: Vec<String> = std::env::args().skip(1).collect();
+	let Some((command, args)) = args.split_first() else {
+		eprintln!("{USAGE}");
+		return ExitCode::FAILURE
+	};
+
+	let result = match (command.as_str(), args) {
+		("ls", []) => Commands::ls(None),
+		("ls", [folder]) => Commands::ls(Some(folder)),
+		("new", [note, text @ ..]) => Commands::create(note, text),
+		("cat", [note]) => Commands::cat(note),
+		("edit", [note]) => Commands::edit(note),
+		("mv", [path, new_path]) => Commands::mv(path, new_path),
+		("rm", [path]) => Commands::rm(path),
+		("pin", [path]) => Commands::set_is_pinned(path, true),
+		("unpin", [path]) => Commands::set_is_pinned(path, false),
+		("search", [_, ..]) => Commands::search(&args.join(" ")),
+		("help" | "-h" | "--help", _) => {
+			println!("{USAGE}");
+			Ok(())
+		},
+		_ => {
+			eprintln!("{USAGE}");
+			return ExitCode::FAILURE
+		}
+	};
+
+	match result {
+		Ok(()) => ExitCode::SUCCESS,
+		Err(e) => {
+			eprintln!("bellbird: {e}");
+			ExitCode::FAILURE
+		}
+	}
+}
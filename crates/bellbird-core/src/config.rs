@@ -1,11 +1,36 @@
 //use async_std::path::PathBuf;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
 
 use configparser::ini::Ini;
 use ::directories::BaseDirs;
 use regex::Regex;
 use anyhow::Result;
 
+use crate::notes::note_paths;
+use crate::vault::Vault;
+
+const DEFAULT_NOTES_DIRECTORY: &str = "BellbirdNotes";
+const DEFAULT_FONT_SIZE: u32 = 13;
+pub const FONT_SIZE_RANGE: RangeInclusive<u32> = 8..=32;
+const DEFAULT_SIDEBAR_WIDTH: i32 = 190;
+const DEFAULT_NOTES_LIST_WIDTH: i32 = 195;
+// the sidebar and the notes list share the range
+pub const PANEL_WIDTH_RANGE: RangeInclusive<i32> = 150..=500;
+const DEFAULT_WINDOW_SIZE: (i32, i32) = (1000, 600);
+const MIN_WINDOW_SIZE: (i32, i32) = (400, 300);
+
+static SETTINGS: OnceLock<Mutex<Settings>> = OnceLock::new();
+// parsed config and metainfos files by path, reads are served from here
+// and every write goes through it. A file is parsed again once another
+// process, e.g. the `bellbird` command, changed it
+static INI_CACHE: OnceLock<Mutex<HashMap<PathBuf, CachedIni>>> = OnceLock::new();
+// the file's modification time when it was parsed
+type CachedIni = (Option<SystemTime>, Ini);
+
 #[derive(Debug)]
 pub enum ConfigSections {
 	General,
@@ -13,6 +38,7 @@ pub enum ConfigSections {
 	NotesList,
 	BreadCrumb,
 	Menu,
+	Window,
 }
 
 impl ConfigSections {
@@ -23,12 +49,14 @@ impl ConfigSections {
 			ConfigSections::NotesList => "NotesList",
 			ConfigSections::BreadCrumb => "BreadCrumb",
 			ConfigSections::Menu => "Menu",
+			ConfigSections::Window => "Window",
 		}
 	}
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum ConfigOptions {
+	ActiveNotebook,
 	DefaultNotesDirectory,
 	UserNotesDirectory,
 	DefaultFontSize,
@@ -37,13 +65,18 @@ pub enum ConfigOptions {
 	OpenNotes,
 	Visible,
 	Width,
+	Height,
+	Maximized,
 	CaretPosition,
 	Pinned,
+	Expanded,
+	SortOrder,
 }
 
 impl ConfigOptions {
 	fn as_str(&self) -> &str {
 		match self {
+			ConfigOptions::ActiveNotebook => "ActiveNotebook",
 			ConfigOptions::DefaultNotesDirectory => "DefaultNotesDirectory",
 			ConfigOptions::UserNotesDirectory => "UserNotesDirectory",
 			ConfigOptions::DefaultFontSize => "DefaultFontSize",
@@ -52,8 +85,12 @@ impl ConfigOptions {
 			ConfigOptions::OpenNotes => "OpenNotes",
 			ConfigOptions::Visible => "Visible",
 			ConfigOptions::Width => "Width",
+			ConfigOptions::Height => "Height",
+			ConfigOptions::Maximized => "Maximized",
 			ConfigOptions::CaretPosition => "CaretPosition",
 			ConfigOptions::Pinned => "Pinned",
+			ConfigOptions::Expanded => "Expanded",
+			ConfigOptions::SortOrder => "SortOrder",
 		}
 	}
 }
@@ -62,6 +99,8 @@ impl ConfigOptions {
 pub struct Config {
 	ini: Ini,
 	ini_file: PathBuf,
+	// see `Vault::config`
+	meta_info_file: Option<PathBuf>,
 }
 
 impl<'a> Config {
@@ -71,6 +110,16 @@ impl<'a> Config {
 		Self {
 			ini,
 			ini_file: PathBuf::new(),
+			meta_info_file: None,
+		}
+	}
+
+	// metainfos are read from and written to `meta_info_file` instead
+	// of the app's metainfos file, the config file stays the same
+	pub fn with_meta_info_file(meta_info_file: &Path) -> Self {
+		Self {
+			meta_info_file: Some(meta_info_file.to_path_buf()),
+			..Self::new()
 		}
 	}
 
@@ -110,24 +159,31 @@ impl<'a> Config {
 		directory_name.to_lowercase()
 	}
 
+	// created on the first run
 	fn config_dir(&self) -> Result<PathBuf> {
 		if let Some(base_dirs) = BaseDirs::new() {
 			let os_config_dir = base_dirs.config_dir().display().to_string();
 			let app_config_dir = self.application_directory_name();
-			return Ok(PathBuf::from(&format!("{os_config_dir}/{app_config_dir}")))
+			let config_dir = PathBuf::from(&format!("{os_config_dir}/{app_config_dir}"));
+			if !config_dir.is_dir() {
+				std::fs::create_dir_all(&config_dir).map_err(|e| anyhow::anyhow!(
+					"Could not create config directory {}: {}",
+					config_dir.display(),
+					e
+				))?;
+			}
+			return Ok(config_dir)
 		}
 		Err(anyhow::anyhow!("Could not find config directory."))
 	}
 
 	pub fn config_file(&self, is_meta_info: bool) -> Result<PathBuf, anyhow::Error> {
+		if let (true, Some(meta_info_file)) = (is_meta_info, &self.meta_info_file) {
+			return Ok(meta_info_file.clone())
+		}
+
 		match self.config_dir() {
 			Ok(config_dir) => {
-				if std::path::Path::new(&config_dir).is_dir() == false {
-					return Err(anyhow::anyhow!(
-						"Could not find config directory: {}",
-						config_dir.display().to_string()
-					));
-				}
 
 				let mut filename = self.application_directory_name();
 
@@ -154,14 +210,15 @@ impl<'a> Config {
 		self.value(section, option, true)
 	}
 
-	fn load_file_async(&mut self, is_meta_info: bool) {
+	fn load_file(&mut self, is_meta_info: bool) {
 		self.ini_file = self.config_file(is_meta_info).unwrap();
-		let _ = self.ini.load_async(&self.ini_file);
+		self.ini = with_cached_ini(&self.ini_file, |ini| ini.clone());
 	}
 
-	fn load_file(&mut self, is_meta_info: bool) {
-		self.ini_file = self.config_file(is_meta_info).unwrap();
-		let _ = self.ini.load(&self.ini_file);
+	fn write_file(&self) -> Result<()> {
+		self.ini.write(&self.ini_file)?;
+		cache_ini(&self.ini_file, &self.ini);
+		Ok(())
 	}
 
 	pub fn config_value(
@@ -179,22 +236,22 @@ impl<'a> Config {
 		is_meta_info: bool,
 		//file: &str
 	) -> Option<String> {
-		if let Ok(config_file) = self.config_file(is_meta_info) {
-			if !config_file.exists() && !config_file.is_file() {
-				return None
-			}
+		let config_file = self.config_file(is_meta_info).ok()?;
+		with_cached_ini(&config_file, |ini| ini.get(section, option.as_str()))
+	}
 
-			let mut config = Ini::new_cs();
-			let mut config_value = None;
+	// the sections of the config file, not the metainfos
+	pub fn config_sections(&mut self) -> Vec<String> {
+		self.load_file(false);
+		self.ini.sections()
+	}
 
-			if let Ok(_) = config.load(&config_file) {
-				if let Some(value) = config.get(&section, &option.as_str()) {
-					config_value = Some(value.to_string());
-				}
-			}
-			return Some(config_value)?
+	pub fn remove_config_section(&mut self, section: &str) -> Result<()> {
+		self.load_file(false);
+		if self.ini.remove_section(section).is_some() {
+			self.write_file()?;
 		}
-		None
+		Ok(())
 	}
 
 	pub fn sections_by_value(
@@ -217,6 +274,120 @@ impl<'a> Config {
 		return Some(sections);
 	}
 
+	// copies the metainfos of a note or folder and of everything
+	// inside it over to `new_path`, e.g. for a duplicate
+	pub fn copy_meta_infos(&mut self, path: &str, new_path: &str) -> Result<()> {
+		self.transfer_meta_infos(path, new_path, false)
+	}
+
+	// same as `copy_meta_infos` but the old entries are dropped, e.g. after a rename
+	pub fn move_meta_infos(&mut self, path: &str, new_path: &str) -> Result<()> {
+		self.transfer_meta_infos(path, new_path, true)
+	}
+
+	// drops the metainfos of a note or folder and of everything inside it
+	pub fn remove_meta_infos(&mut self, path: &str) -> Result<()> {
+		self.load_file(true);
+
+		let sections = self.nested_sections(path);
+		if sections.is_empty() {
+			return Ok(())
+		}
+		for section in sections {
+			self.ini.remove_section(&section);
+		}
+		self.write_file()?;
+		Ok(())
+	}
+
+	// notes used to be keyed by path like folders, their sections are
+	// moved over to their ids in `vault`. Returns how many sections were moved
+	pub fn migrate_meta_infos_to_ids(&mut self, vault: &Vault) -> Result<usize> {
+		self.load_file(true);
+
+		let note_sections: Vec<(String, String)> = self.ini
+			.get_map_ref()
+			.keys()
+			.filter(|section| std::path::Path::new(section.as_str()).is_file())
+			.filter_map(|section| {
+				let id = vault.note_id(std::path::Path::new(section))?;
+				Some((section.clone(), id))
+			})
+			.collect();
+
+		for (section, id) in note_sections.iter() {
+			self.move_meta_infos(section, id)?;
+		}
+		Ok(note_sections.len())
+	}
+
+	// drops metainfos of folders in `vault` that no longer exist and of
+	// ids no note has anymore, e.g. after files were deleted outside of the
+	// app. Other notebooks share the metainfos file, their folders and
+	// `kept_ids` stay untouched. Returns how many sections were removed
+	pub fn collect_stale_meta_infos(&mut self, vault: &Vault, kept_ids: &HashSet<String>) -> Result<usize> {
+		// a missing or unmounted notes directory is created empty, which
+		// must not cost every note its metainfos
+		if note_paths(vault.root()).next().is_none() {
+			return Ok(0)
+		}
+		self.load_file(true);
+
+		let stale_sections: Vec<String> = self.ini
+			.get_map_ref()
+			.keys()
+			.filter(|section| {
+				let path = std::path::Path::new(section.as_str());
+				match path.is_absolute() {
+					true => path.starts_with(vault.root()) && !path.exists(),
+					false => vault.note_path(section).is_none() && !kept_ids.contains(section.as_str()),
+				}
+			})
+			.cloned()
+			.collect();
+
+		if stale_sections.is_empty() {
+			return Ok(0)
+		}
+		for section in stale_sections.iter() {
+			self.ini.remove_section(section);
+		}
+		self.write_file()?;
+		Ok(stale_sections.len())
+	}
+
+	// the section of `path` itself and the ones of everything inside it
+	fn nested_sections(&self, path: &str) -> Vec<String> {
+		let nested_prefix = format!("{path}/");
+		self.ini
+			.get_map_ref()
+			.keys()
+			.filter(|section| section.as_str() == path || section.starts_with(&nested_prefix))
+			.cloned()
+			.collect()
+	}
+
+	fn transfer_meta_infos(&mut self, path: &str, new_path: &str, remove_old: bool) -> Result<()> {
+		self.load_file(true);
+
+		let sections = self.nested_sections(path);
+		if sections.is_empty() {
+			return Ok(())
+		}
+		for section in sections {
+			let options = match remove_old {
+				true => self.ini.remove_section(&section),
+				false => self.ini.get_map_ref().get(&section).cloned(),
+			};
+			let new_section = format!("{new_path}{}", &section[path.len()..]);
+			for (option, value) in options.unwrap_or_default() {
+				self.ini.set(&new_section, &option, value);
+			}
+		}
+		self.write_file()?;
+		Ok(())
+	}
+
 	pub async fn set_config_value_async(
 		&mut self,
 		section: &str,
@@ -260,12 +431,10 @@ impl<'a> Config {
 		value: String,
 		is_meta_info: bool
 	) -> Result<()> {
-		self.load_file_async(is_meta_info);
-		// read the existing config because it sometimes gets truncated
-		let outstring = self.ini.writes();
-		let _ = self.ini.read(outstring);
+		self.load_file(is_meta_info);
 		self.ini.set(section, option.as_str(), Some(value.clone()));
-		let _ = self.ini.write_async(&self.ini_file).await;
+		self.ini.write_async(&self.ini_file).await?;
+		cache_ini(&self.ini_file, &self.ini);
 		Ok(())
 	}
 
@@ -277,11 +446,205 @@ impl<'a> Config {
 		is_meta_info: bool
 	) -> Result<()> {
 		self.load_file(is_meta_info);
-		// read the existing config because it sometimes gets truncated
-		let outstring = self.ini.writes();
-		let _ = self.ini.read(outstring);
 		self.ini.set(section, option.as_str(), Some(value.clone()));
-		let _ = self.ini.write(&self.ini_file);
+		self.write_file()
+	}
+
+	// all settings in one go, see `Settings::save`
+	fn set_settings(&mut self, settings: &Settings) -> Result<()> {
+		let values = [
+			(ConfigSections::General, ConfigOptions::UserNotesDirectory, settings.notes_directory.display().to_string()),
+			(ConfigSections::General, ConfigOptions::DefaultFontSize, settings.font_size.to_string()),
+			(ConfigSections::SideBar, ConfigOptions::Width, settings.sidebar_width.to_string()),
+			(ConfigSections::SideBar, ConfigOptions::Visible, settings.sidebar_visible.to_string()),
+			(ConfigSections::NotesList, ConfigOptions::Width, settings.notes_list_width.to_string()),
+			(ConfigSections::NotesList, ConfigOptions::Visible, settings.notes_list_visible.to_string()),
+			(ConfigSections::BreadCrumb, ConfigOptions::Visible, settings.breadcrumb_visible.to_string()),
+			(ConfigSections::Window, ConfigOptions::Width, settings.window_width.to_string()),
+			(ConfigSections::Window, ConfigOptions::Height, settings.window_height.to_string()),
+			(ConfigSections::Window, ConfigOptions::Maximized, settings.window_maximized.to_string()),
+		];
+		for (section, option, value) in values {
+			self.set_config_value(section.as_str(), option, value)?;
+		}
+		Ok(())
+	}
+}
+
+// the settings users get to change, typed and validated. Values that are
+// missing or invalid in the config file fall back to the defaults
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+	// relative to the home directory unless it is absolute
+	pub notes_directory: PathBuf,
+	pub font_size: u32,
+	pub sidebar_width: i32,
+	pub sidebar_visible: bool,
+	pub notes_list_width: i32,
+	pub notes_list_visible: bool,
+	pub breadcrumb_visible: bool,
+	// the size of the window when it is not maximised
+	pub window_width: i32,
+	pub window_height: i32,
+	pub window_maximized: bool,
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		Self {
+			notes_directory: PathBuf::from(DEFAULT_NOTES_DIRECTORY),
+			font_size: DEFAULT_FONT_SIZE,
+			sidebar_width: DEFAULT_SIDEBAR_WIDTH,
+			sidebar_visible: true,
+			notes_list_width: DEFAULT_NOTES_LIST_WIDTH,
+			notes_list_visible: true,
+			breadcrumb_visible: true,
+			window_width: DEFAULT_WINDOW_SIZE.0,
+			window_height: DEFAULT_WINDOW_SIZE.1,
+			window_maximized: false,
+		}
+	}
+}
+
+impl Settings {
+	// read from the config file once, later calls get the cached settings
+	pub fn get() -> Settings {
+		match Self::cached().lock() {
+			Ok(settings) => settings.clone(),
+			Err(_) => Settings::default()
+		}
+	}
+
+	pub fn save(&self) -> Result<()> {
+		self.validate()?;
+		Config::new().set_settings(self)?;
+		if let Ok(mut settings) = Self::cached().lock() {
+			*settings = self.clone();
+		}
 		Ok(())
 	}
+
+	pub fn validate(&self) -> Result<()> {
+		if self.notes_directory.as_os_str().is_empty() {
+			return Err(anyhow::anyhow!("The notes directory must not be empty"))
+		}
+		if !FONT_SIZE_RANGE.contains(&self.font_size) {
+			return Err(anyhow::anyhow!(
+				"The font size must be between {} and {}",
+				FONT_SIZE_RANGE.start(),
+				FONT_SIZE_RANGE.end()
+			))
+		}
+		if !PANEL_WIDTH_RANGE.contains(&self.sidebar_width) {
+			return Err(anyhow::anyhow!(
+				"The sidebar width must be between {} and {}",
+				PANEL_WIDTH_RANGE.start(),
+				PANEL_WIDTH_RANGE.end()
+			))
+		}
+		if !PANEL_WIDTH_RANGE.contains(&self.notes_list_width) {
+			return Err(anyhow::anyhow!(
+				"The notes list width must be between {} and {}",
+				PANEL_WIDTH_RANGE.start(),
+				PANEL_WIDTH_RANGE.end()
+			))
+		}
+		if self.window_width < MIN_WINDOW_SIZE.0 || self.window_height < MIN_WINDOW_SIZE.1 {
+			return Err(anyhow::anyhow!(
+				"The window must be at least {}x{}",
+				MIN_WINDOW_SIZE.0,
+				MIN_WINDOW_SIZE.1
+			))
+		}
+		Ok(())
+	}
+
+	fn cached() -> &'static Mutex<Settings> {
+		SETTINGS.get_or_init(|| Mutex::new(Self::load()))
+	}
+
+	// a missing config file is created with the defaults on the first run
+	fn load() -> Settings {
+		let config = Config::new();
+		let defaults = Settings::default();
+		let is_first_run = config.config_file(false).is_ok_and(|file| !file.exists());
+		if is_first_run {
+			if let Err(e) = Config::new().set_settings(&defaults) {
+				eprintln!("Could not create config file: {:?}", e);
+			}
+			return defaults
+		}
+
+		let value = |section: ConfigSections, option: ConfigOptions| {
+			config.config_value(section.as_str(), option)
+		};
+		Settings {
+			notes_directory: value(ConfigSections::General, ConfigOptions::UserNotesDirectory)
+				.filter(|directory| !directory.trim().is_empty())
+				.map(PathBuf::from)
+				.unwrap_or(defaults.notes_directory),
+			font_size: value(ConfigSections::General, ConfigOptions::DefaultFontSize)
+				.and_then(|size| size.parse().ok())
+				.filter(|size| FONT_SIZE_RANGE.contains(size))
+				.unwrap_or(defaults.font_size),
+			sidebar_width: value(ConfigSections::SideBar, ConfigOptions::Width)
+				.and_then(|width| width.parse().ok())
+				.filter(|width| PANEL_WIDTH_RANGE.contains(width))
+				.unwrap_or(defaults.sidebar_width),
+			sidebar_visible: value(ConfigSections::SideBar, ConfigOptions::Visible)
+				.and_then(|visible| visible.parse().ok())
+				.unwrap_or(defaults.sidebar_visible),
+			notes_list_width: value(ConfigSections::NotesList, ConfigOptions::Width)
+				.and_then(|width| width.parse().ok())
+				.filter(|width| PANEL_WIDTH_RANGE.contains(width))
+				.unwrap_or(defaults.notes_list_width),
+			notes_list_visible: value(ConfigSections::NotesList, ConfigOptions::Visible)
+				.and_then(|visible| visible.parse().ok())
+				.unwrap_or(defaults.notes_list_visible),
+			breadcrumb_visible: value(ConfigSections::BreadCrumb, ConfigOptions::Visible)
+				.and_then(|visible| visible.parse().ok())
+				.unwrap_or(defaults.breadcrumb_visible),
+			window_width: value(ConfigSections::Window, ConfigOptions::Width)
+				.and_then(|width| width.parse().ok())
+				.filter(|width| *width >= MIN_WINDOW_SIZE.0)
+				.unwrap_or(defaults.window_width),
+			window_height: value(ConfigSections::Window, ConfigOptions::Height)
+				.and_then(|height| height.parse().ok())
+				.filter(|height| *height >= MIN_WINDOW_SIZE.1)
+				.unwrap_or(defaults.window_height),
+			window_maximized: value(ConfigSections::Window, ConfigOptions::Maximized)
+				.and_then(|maximized| maximized.parse().ok())
+				.unwrap_or(defaults.window_maximized),
+		}
+	}
+}
+
+fn with_cached_ini<T>(file: &Path, f: impl FnOnce(&Ini) -> T) -> T {
+	let load = || {
+		let mut ini = Ini::new_cs();
+		let _ = ini.load(file);
+		ini
+	};
+	let Ok(mut cache) = INI_CACHE.get_or_init(|| Mutex::new(HashMap::new())).lock() else {
+		return f(&load())
+	};
+
+	let modified = modified_time(file);
+	let is_outdated = cache
+		.get(file)
+		.map_or(true, |(cached_modified, _)| *cached_modified != modified);
+	if is_outdated {
+		cache.insert(file.to_path_buf(), (modified, load()));
+	}
+	f(&cache[file].1)
+}
+
+fn cache_ini(file: &Path, ini: &Ini) {
+	if let Ok(mut cache) = INI_CACHE.get_or_init(|| Mutex::new(HashMap::new())).lock() {
+		cache.insert(file.to_path_buf(), (modified_time(file), ini.clone()));
+	}
+}
+
+fn modified_time(file: &Path) -> Option<SystemTime> {
+	std::fs::metadata(file).and_then(|metadata| metadata.modified()).ok()
 }
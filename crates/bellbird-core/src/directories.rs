@@ -4,7 +4,10 @@ use directories::UserDirs;
 use walkdir::WalkDir;
 use anyhow::Result;
 
-use crate::config::{Config, ConfigOptions, ConfigSections};
+use crate::config::{Config, ConfigOptions, ConfigSections, Settings};
+use crate::journal::Operation;
+use crate::vault::Vault;
+use crate::watcher::Watcher;
 
 #[derive(Debug, Clone)]
 pub struct Directory {
@@ -13,18 +16,217 @@ pub struct Directory {
 	pub children: Vec<Directory>,
 	//pub nbr_notes: usize,
 	//pub nbr_folders: usize,
-	//pub is_expanded: bool,
+	pub is_expanded: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct Directories;
 
+impl Vault {
+	// the folders inside `path`, `max_depth` levels deep
+	pub fn directories(&self, path: &Path, max_depth: usize) -> Option<Vec<Directory>> {
+		let expanded_directories = self.expanded_directories();
+		Directories::list_with_children(path, max_depth, &expanded_directories)
+	}
+
+	// how many folders `path` is nested below the root
+	pub fn depth(&self, path: &Path) -> u32 {
+		let walk_dir_iter = WalkDir::new(self.root())
+			.min_depth(1)
+			.max_depth(20)
+			.into_iter();
+
+		for entry in walk_dir_iter {
+			if let Ok(dir) = entry {
+				if path == dir.path() {
+					return (dir.depth() - 1) as u32;
+				}
+			}
+		}
+		0
+	}
+
+	pub fn create_directory(&self, path: &Path) -> Result<(), std::io::Error> {
+		fs::create_dir(path).map_err(|e| {
+			eprintln!("Failed to create directory: {:?}", e);
+			e
+		})?;
+
+		self.record(Operation::Create { path: path.to_path_buf(), is_directory: true });
+		Ok(())
+	}
+
+	pub fn rename_directory(&self, old_path: &Path, new_path: &Path) -> Result<(), std::io::Error> {
+		// an empty folder of that name would be replaced without a word
+		if new_path != old_path && new_path.exists() {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::AlreadyExists,
+				format!("´{}´ already exists", new_path.display())
+			));
+		}
+		fs::rename(old_path, new_path).map_err(|e| {
+			eprintln!("Failed to rename directory: {:?}", e);
+			e
+		})?;
+
+		let _ = self.config().move_meta_infos(
+			&old_path.display().to_string(),
+			&new_path.display().to_string()
+		);
+		self.reindex_directory(old_path);
+		self.reindex_directory(new_path);
+		self.reindex_note_ids(old_path);
+		self.reindex_note_ids(new_path);
+		self.record(Operation::Rename {
+			old_path: old_path.to_path_buf(),
+			new_path: new_path.to_path_buf(),
+			is_directory: true,
+		});
+		Ok(())
+	}
+
+	// moves the folder with everything inside it into another folder
+	pub fn move_directory(&self, path: &Path, directory: &Path) -> Result<PathBuf> {
+		let Some(name) = path.file_name() else {
+			return Err(anyhow::anyhow!("Could not move folder, invalid path: {}", path.display()));
+		};
+
+		let new_path = directory.join(name);
+		if new_path == path {
+			return Ok(new_path)
+		}
+		if directory.starts_with(path) {
+			return Err(anyhow::anyhow!("Could not move folder into itself: {}", path.display()));
+		}
+		if new_path.exists() {
+			return Err(anyhow::anyhow!(
+				"Could not move folder, ´{}´ already exists in ´{}´",
+				name.to_string_lossy(),
+				directory.display()
+			));
+		}
+
+		self.rename_directory(path, &new_path)?;
+		Ok(new_path)
+	}
+
+	// copies the folder with everything inside it next to the original
+	pub fn duplicate_directory(&self, path: &Path) -> Result<PathBuf> {
+		let new_path = copy_path(path);
+
+		for entry in WalkDir::new(path).into_iter().filter_entry(|e| !Directories::is_hidden(e)) {
+			let entry = entry?;
+			let target = new_path.join(entry.path().strip_prefix(path)?);
+			match entry.file_type().is_dir() {
+				true => fs::create_dir_all(&target)?,
+				false => {
+					fs::copy(entry.path(), &target)?;
+				}
+			}
+		}
+
+		let mut config = self.config();
+		let _ = config.copy_meta_infos(
+			&path.display().to_string(),
+			&new_path.display().to_string()
+		);
+		// the copied notes get ids of their own, their metainfos come along
+		for entry in WalkDir::new(path).into_iter().filter_entry(|e| !Directories::is_hidden(e)).flatten() {
+			if !entry.file_type().is_file() {
+				continue
+			}
+			let original = entry.path();
+			let copy = new_path.join(original.strip_prefix(path)?);
+			if let (Some(id), Some(copy_id)) = (self.note_id(original), self.note_id(&copy)) {
+				let _ = config.copy_meta_infos(&id, &copy_id);
+			}
+		}
+		self.reindex_directory(&new_path);
+		self.record(Operation::Create { path: new_path.clone(), is_directory: true });
+		Ok(new_path)
+	}
+
+	// folders are moved to the trash, without `delete_files`
+	// only empty folders can be deleted
+	pub fn delete_directory(&self, path: &Path, delete_files: bool) -> Result<(), std::io::Error> {
+		if !delete_files && fs::read_dir(path)?.next().is_some() {
+			let e = std::io::Error::other(
+				format!("Directory not empty: {}", path.display())
+			);
+			eprintln!("Failed to delete directory: {:?}", e);
+			return Err(e);
+		}
+
+		let trashed_path = self.move_to_trash(path).map_err(|e| {
+			eprintln!("Failed to move directory to trash: {:?}", e);
+			std::io::Error::other(e.to_string())
+		})?;
+
+		self.record(Operation::Delete { path: path.to_path_buf(), trashed_path });
+		Ok(())
+	}
+
+	pub fn is_expanded(&self, path: &Path) -> bool {
+		let config = self.config();
+		let path = path.display().to_string();
+		match config.meta_info(&path, ConfigOptions::Expanded) {
+			Some(value) => value == "true",
+			None => false
+		}
+	}
+
+	pub fn set_is_expanded(&self, path: &Path, is_expanded: bool) -> Result<()> {
+		let is_expanded = if is_expanded { "true" } else { "false" };
+		self.config().set_meta_value(
+			&path.display().to_string(),
+			ConfigOptions::Expanded,
+			is_expanded.to_string()
+		)
+	}
+
+	// pinned folders anywhere in the tree, sorted by name
+	pub fn pinned_directories(&self) -> Vec<PathBuf> {
+		let mut config = self.config();
+		let mut directories: Vec<PathBuf> = config
+			.sections_by_value(ConfigOptions::Pinned, "true".to_string())
+			.unwrap_or_default()
+			.iter()
+			.map(PathBuf::from)
+			// other notebooks share the metainfos file
+			.filter(|path| path.starts_with(self.root()) && path.is_dir())
+			.collect();
+
+		directories.sort_by_key(|path| {
+			path.file_name().map(|name| name.to_string_lossy().to_lowercase())
+		});
+		directories
+	}
+
+	pub fn expanded_directories(&self) -> Vec<String> {
+		let mut config = self.config();
+		config
+			.sections_by_value(ConfigOptions::Expanded, "true".to_string())
+			.unwrap_or_default()
+			.into_iter()
+			.filter(|path| Path::new(path).starts_with(self.root()))
+			.collect()
+	}
+}
+
 impl Directories {
 	pub fn list(path: &Path, max_depth: usize) -> Option<Vec<Directory>> {
+		Vault::current().directories(path, max_depth)
+	}
+
+	fn list_with_children(
+		path: &Path,
+		max_depth: usize,
+		expanded_directories: &Vec<String>
+	) -> Option<Vec<Directory>> {
 		let mut directories: Vec<Directory> = vec![];
 		let walk_dir_iter = WalkDir::new(path)
 			.min_depth(1)
-			.max_depth(max_depth)
+			.max_depth(1)
 			.into_iter();
 
 		for entry in walk_dir_iter.filter_entry(|e| !Self::is_hidden(e)) {
@@ -35,12 +237,16 @@ impl Directories {
 				continue;
 			}
 
+			let children = match max_depth > 1 {
+				true => Self::list_with_children(path, max_depth - 1, expanded_directories)?,
+				false => vec![]
+			};
+
 			directories.push(Directory {
 				name: path.file_name().unwrap().to_str().unwrap().to_string(),
 				path: path.to_path_buf(),
-				// get direct children for now
-				//children: Self::list(path, 1)?
-				children: vec![]
+				children,
+				is_expanded: expanded_directories.contains(&path.display().to_string()),
 			})
 		}
 
@@ -51,19 +257,18 @@ impl Directories {
 	}
 
 	pub fn bb_root_directory() -> Option<PathBuf> {
+		Self::notes_root(&Settings::get().notes_directory)
+	}
+
+	// `directory` is relative to the home directory unless it is absolute
+	pub fn notes_root(directory: &Path) -> Option<PathBuf> {
 		if let Some(home_dir) = Self::home_directory() {
 			let mut root_dir = PathBuf::from(home_dir);
 
-			let config = Config::new();
-			let value = config.config_value(
-				ConfigSections::General.as_str(),
-				ConfigOptions::UserNotesDirectory,
-			)?;
-
-			root_dir.push(value);
+			root_dir.push(directory);
 
 			if !root_dir.exists() {
-				let _=  Directories::create(root_dir.as_path());
+				let _ = fs::create_dir_all(root_dir.as_path());
 			}
 
 			return Some(root_dir);
@@ -71,6 +276,15 @@ impl Directories {
 		None
 	}
 
+	// the watcher moves over to the notes directory after it was changed
+	// in the settings, `Vault::current` comes with fresh indexes by itself
+	pub fn root_changed() {
+		let Some(root) = Self::bb_root_directory() else {
+			return
+		};
+		Watcher::set_root(&root);
+	}
+
 	fn home_directory() -> Option<PathBuf> {
 		match UserDirs::new() {
 			Some(user_dirs) => {
@@ -100,72 +314,86 @@ impl Directories {
 	}
 
 	pub fn get_depth_from_root(path: &Path) -> u32 {
-		if let Some(root_dir) = Self::bb_root_directory() {
-			let walk_dir_iter = WalkDir::new(root_dir)
-				.min_depth(1)
-				.max_depth(20)
-				.into_iter();
-
-			for entry in walk_dir_iter {
-				if let Ok(dir) = entry {
-					if path == dir.path() {
-						return (dir.depth() - 1) as u32;
-					}
-				}
-			}
-		}
-		0
+		Vault::current().depth(path)
 	}
 
 	pub fn dir_has_children(path: &Path) -> bool {
 		if !path.exists() {
 			return false;
 		}
-		match Self::list(path, 1) {
+		match Self::list_with_children(path, 1, &vec![]) {
 			Some(entries) => entries.len() > 0,
 			None => false
 		}
 	}
 
 	pub fn create(path: &Path) -> Result<(), std::io::Error> {
-		fs::create_dir(path).map_err(|e| {
-			eprintln!("Failed to create directory: {:?}", e);
-			e
-		})
+		Vault::current().create_directory(path)
 	}
 
 	pub fn rename(old_path: &Path, new_path: &Path) -> Result<(), std::io::Error> {
-		fs::rename(old_path, new_path).map_err(|e| {
-			eprintln!("Failed to rename directory: {:?}", e);
-			e
-		})
+		Vault::current().rename_directory(old_path, new_path)
 	}
 
-	pub fn delete(path: &Path, mut delete_files: bool) -> Result<(), std::io::Error> {
-		if !delete_files {
-			delete_files = false
-		}
+	pub fn move_to(path: &Path, directory: &Path) -> Result<PathBuf> {
+		Vault::current().move_directory(path, directory)
+	}
 
-		//remove_section(&path, true);
+	pub fn duplicate(path: &Path) -> Result<PathBuf> {
+		Vault::current().duplicate_directory(path)
+	}
 
-		if !delete_files {
-			fs::remove_dir(path).map_err(|e| {
-				eprintln!("Failed to delete directory: {:?}", e);
-				e
-			})
-		}
-		else {
-			fs::remove_dir_all(path).map_err(|e| {
-				eprintln!("Failed to recursively delete directory: {:?}", e);
-				e
-			})
-		}
+	pub fn delete(path: &Path, delete_files: bool) -> Result<(), std::io::Error> {
+		Vault::current().delete_directory(path, delete_files)
+	}
+
+	pub fn is_expanded(path: &Path) -> bool {
+		Vault::current().is_expanded(path)
+	}
+
+	pub fn set_is_expanded(path: &Path, is_expanded: bool) {
+		let _ = Vault::current().set_is_expanded(path, is_expanded);
 	}
 
-	fn is_hidden(entry: &walkdir::DirEntry) -> bool {
+	// pinning works the same for folders as it does for notes
+	pub fn is_pinned(path: &Path) -> bool {
+		Vault::current().is_pinned(path)
+	}
+
+	pub fn set_is_pinned(path: &Path, is_pinned: bool) {
+		let _ = Vault::current().set_is_pinned(path, is_pinned);
+	}
+
+	pub fn pinned_directories() -> Vec<PathBuf> {
+		Vault::current().pinned_directories()
+	}
+
+	pub fn expanded_directories() -> Option<Vec<String>> {
+		Some(Vault::current().expanded_directories())
+	}
+
+	pub(crate) fn is_hidden(entry: &walkdir::DirEntry) -> bool {
 		entry.file_name()
 			.to_str()
 			.map(|s| s.starts_with("."))
 			.unwrap_or(false)
 	}
 }
+
+// `Copy of Name`, `Copy of Name 2` and so on, whichever is still free
+pub(crate) fn copy_path(path: &Path) -> PathBuf {
+	let name = path.file_stem()
+		.map(|stem| stem.to_string_lossy().to_string())
+		.unwrap_or_default();
+	let extension = path.extension()
+		.map(|extension| format!(".{}", extension.to_string_lossy()))
+		.unwrap_or_default();
+
+	let mut copy_name = format!("Copy of {name}");
+	let mut number = 2;
+	while path.with_file_name(format!("{copy_name}{extension}")).exists() {
+		copy_name = format!("Copy of {name} {number}");
+		number += 1;
+	}
+	path.with_file_name(format!("{copy_name}{extension}"))
+}
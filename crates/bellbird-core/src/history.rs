@@ -0,0 +1,495 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+
+use crate::vault::Vault;
+
+// revisions of a note live in `<root>/.history/<note id>/`, one file per
+// revision named after its unix timestamp. Being keyed by id the history
+// stays with the note when it is renamed or moved
+const HISTORY_DIRECTORY: &str = ".history";
+const REVISION_EXTENSION: &str = "rev";
+
+// a new revision is taken at most every few minutes while typing
+const SNAPSHOT_INTERVAL: i64 = 5 * 60;
+const HOUR: i64 = 60 * 60;
+const DAY: i64 = 24 * HOUR;
+const MAX_AGE: i64 = 90 * DAY;
+
+// lines both sides of a merge changed differently end up between these
+const CONFLICT_START: &str = "<<<<<<< ours";
+const CONFLICT_SEPARATOR: &str = "=======";
+const CONFLICT_END: &str = ">>>>>>> theirs";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Revision {
+	pub path: PathBuf,
+	// unix timestamp in seconds
+	pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Merge {
+	pub content: String,
+	// how many places hold both versions between conflict markers
+	pub conflicts: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffLine {
+	Equal(String),
+	Added(String),
+	Removed(String),
+}
+
+impl Vault {
+	pub fn history_directory(&self) -> PathBuf {
+		self.root().join(HISTORY_DIRECTORY)
+	}
+
+	fn revisions_directory(&self, note_path: &Path) -> Option<PathBuf> {
+		let revisions_dir = self.history_directory().join(self.note_id(note_path)?);
+		if !revisions_dir.exists() {
+			self.migrate_revisions(note_path, &revisions_dir);
+		}
+		Some(revisions_dir)
+	}
+
+	// revisions used to be stored under the note's path relative to the root
+	fn migrate_revisions(&self, note_path: &Path, revisions_dir: &Path) {
+		let Ok(relative_path) = note_path.strip_prefix(self.root()) else {
+			return
+		};
+		let old_dir = self.history_directory().join(relative_path);
+		if old_dir.is_dir() {
+			if let Err(e) = fs::rename(&old_dir, revisions_dir) {
+				eprintln!("Failed to migrate note history: {:?}", e);
+			}
+		}
+	}
+
+	// stores `content` as a new revision unless the latest one is recent
+	// or identical, returns the revision if one was written
+	pub fn snapshot(&self, note_path: &Path, content: &str) -> Result<Option<Revision>> {
+		let timestamp = now();
+		if let Some(latest) = self.revisions(note_path).first() {
+			if timestamp - latest.timestamp < SNAPSHOT_INTERVAL {
+				return Ok(None)
+			}
+			if History::read(latest).is_ok_and(|latest_content| latest_content == content) {
+				return Ok(None)
+			}
+		}
+
+		let revision = self.write_revision(note_path, content, timestamp)?;
+		self.thin_history(note_path);
+		Ok(Some(revision))
+	}
+
+	fn write_revision(&self, note_path: &Path, content: &str, timestamp: i64) -> Result<Revision> {
+		let Some(revisions_dir) = self.revisions_directory(note_path) else {
+			return Err(anyhow::anyhow!(
+				"Could not find the id of note: {}",
+				note_path.display()
+			))
+		};
+		fs::create_dir_all(&revisions_dir)?;
+
+		// two revisions within the same second must not overwrite each other
+		let mut timestamp = timestamp;
+		let mut path = revisions_dir.join(format!("{timestamp}.{REVISION_EXTENSION}"));
+		while path.exists() {
+			timestamp += 1;
+			path = revisions_dir.join(format!("{timestamp}.{REVISION_EXTENSION}"));
+		}
+		fs::write(&path, content)?;
+		Ok(Revision { path, timestamp })
+	}
+
+	// newest first
+	pub fn revisions(&self, note_path: &Path) -> Vec<Revision> {
+		let Some(entries) = self.revisions_directory(note_path)
+			.and_then(|revisions_dir| fs::read_dir(revisions_dir).ok())
+		else {
+			return vec![]
+		};
+
+		let mut revisions: Vec<Revision> = entries
+			.flatten()
+			.map(|entry| entry.path())
+			.filter(|path| path.extension().is_some_and(|ext| ext == REVISION_EXTENSION))
+			.filter_map(|path| {
+				let timestamp = path.file_stem()?.to_str()?.parse().ok()?;
+				Some(Revision { path, timestamp })
+			})
+			.collect();
+
+		revisions.sort_by_key(|revision| Reverse(revision.timestamp));
+		revisions
+	}
+
+	// writes the revision back to the note, the content it replaces is
+	// kept as a revision of its own so a restore can be reverted too
+	pub fn restore_revision(&self, note_path: &Path, revision: &Revision) -> Result<String> {
+		let content = History::read(revision)?;
+		if let Ok(current_content) = fs::read_to_string(note_path) {
+			if current_content != content {
+				self.write_revision(note_path, &current_content, now())?;
+			}
+		}
+
+		self.write_note(note_path, &content)?;
+		Ok(content)
+	}
+
+	// keeps every revision of the last hour, one per hour for the last day
+	// and one per day after that, revisions older than `MAX_AGE` are dropped
+	pub fn thin_history(&self, note_path: &Path) {
+		let timestamp = now();
+		let mut last_bucket = None;
+
+		for revision in self.revisions(note_path) {
+			let age = timestamp - revision.timestamp;
+			let bucket = match age {
+				age if age < HOUR => Some(revision.timestamp),
+				age if age < DAY => Some(revision.timestamp / HOUR),
+				age if age < MAX_AGE => Some(revision.timestamp / DAY),
+				_ => None
+			};
+
+			// revisions are sorted newest first, so the first one
+			// of every bucket is kept and the rest are removed
+			if bucket.is_none() || bucket == last_bucket {
+				let _ = fs::remove_file(&revision.path);
+				continue
+			}
+			last_bucket = bucket;
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct History;
+
+impl History {
+	pub fn history_directory() -> Option<PathBuf> {
+		Some(Vault::current().history_directory())
+	}
+
+	pub fn snapshot(note_path: &Path, content: &str) -> Result<Option<Revision>> {
+		Vault::current().snapshot(note_path, content)
+	}
+
+	pub fn revisions(note_path: &Path) -> Vec<Revision> {
+		Vault::current().revisions(note_path)
+	}
+
+	pub fn read(revision: &Revision) -> Result<String> {
+		Ok(fs::read_to_string(&revision.path)?)
+	}
+
+	pub fn restore(note_path: &Path, revision: &Revision) -> Result<String> {
+		Vault::current().restore_revision(note_path, revision)
+	}
+
+	pub fn thin(note_path: &Path) {
+		Vault::current().thin_history(note_path);
+	}
+
+	// three-way merge of two edits of `base`, e.g. unsaved changes and a
+	// change made on disk. Parts only one side changed take that change,
+	// where both changed the same lines differently both versions are kept
+	// between conflict markers
+	pub fn merge(base: &str, ours: &str, theirs: &str) -> Merge {
+		let base_lines: Vec<&str> = base.lines().collect();
+		let our_lines: Vec<&str> = ours.lines().collect();
+		let their_lines: Vec<&str> = theirs.lines().collect();
+		let our_matches = matched_lines(base, ours);
+		let their_matches = matched_lines(base, theirs);
+
+		let mut merged: Vec<&str> = vec![];
+		let mut conflicts = 0;
+		let (mut base_start, mut our_start, mut their_start) = (0, 0, 0);
+		loop {
+			// the next line of the base both sides kept
+			let stable = (base_start..base_lines.len())
+				.find_map(|line| Some((line, our_matches[line]?, their_matches[line]?)));
+			let (base_end, our_end, their_end) = stable
+				.unwrap_or((base_lines.len(), our_lines.len(), their_lines.len()));
+
+			let base_chunk = &base_lines[base_start..base_end];
+			let our_chunk = &our_lines[our_start..our_end];
+			let their_chunk = &their_lines[their_start..their_end];
+			if our_chunk == base_chunk {
+				merged.extend(their_chunk);
+			}
+			else if their_chunk == base_chunk || our_chunk == their_chunk {
+				merged.extend(our_chunk);
+			}
+			else {
+				merged.push(CONFLICT_START);
+				merged.extend(our_chunk);
+				merged.push(CONFLICT_SEPARATOR);
+				merged.extend(their_chunk);
+				merged.push(CONFLICT_END);
+				conflicts += 1;
+			}
+
+			let Some((base_line, our_line, their_line)) = stable else {
+				break
+			};
+			merged.push(base_lines[base_line]);
+			base_start = base_line + 1;
+			our_start = our_line + 1;
+			their_start = their_line + 1;
+		}
+
+		let mut content = merged.join("\n");
+		if ours.ends_with('\n') || theirs.ends_with('\n') {
+			content.push('\n');
+		}
+		Merge { content, conflicts }
+	}
+
+	// line based diff from `old` to `new` (Myers' algorithm in linear space)
+	pub fn diff(old: &str, new: &str) -> Vec<DiffLine> {
+		let old: Vec<&str> = old.lines().collect();
+		let new: Vec<&str> = new.lines().collect();
+		let mut lines = vec![];
+		diff_lines(&old, &new, &mut lines);
+		lines
+	}
+}
+
+// the lines between the common start and end are split where the shortest
+// edit script crosses its middle, both halves are diffed on their own
+fn diff_lines(old: &[&str], new: &[&str], lines: &mut Vec<DiffLine>) {
+	let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+	let suffix = old[prefix..].iter().rev()
+		.zip(new[prefix..].iter().rev())
+		.take_while(|(a, b)| a == b)
+		.count();
+	let old_middle = &old[prefix..old.len() - suffix];
+	let new_middle = &new[prefix..new.len() - suffix];
+
+	lines.extend(old[..prefix].iter().map(|line| DiffLine::Equal(line.to_string())));
+	match (old_middle.is_empty(), new_middle.is_empty()) {
+		(true, _) => lines.extend(new_middle.iter().map(|line| DiffLine::Added(line.to_string()))),
+		(_, true) => lines.extend(old_middle.iter().map(|line| DiffLine::Removed(line.to_string()))),
+		_ => match middle_snake(old_middle, new_middle) {
+			Some((x, y)) => {
+				diff_lines(&old_middle[..x], &new_middle[..y], lines);
+				diff_lines(&old_middle[x..], &new_middle[y..], lines);
+			},
+			None => {
+				lines.extend(old_middle.iter().map(|line| DiffLine::Removed(line.to_string())));
+				lines.extend(new_middle.iter().map(|line| DiffLine::Added(line.to_string())));
+			}
+		}
+	}
+	lines.extend(old[old.len() - suffix..].iter().map(|line| DiffLine::Equal(line.to_string())));
+}
+
+// where the search from the start and the one from the end meet. Both only
+// keep the furthest point they reached on every diagonal, so the memory
+// needed grows with the number of lines instead of with the edits squared
+fn middle_snake(old: &[&str], new: &[&str]) -> Option<(usize, usize)> {
+	let (n, m) = (old.len() as isize, new.len() as isize);
+	let max_d = (n + m + 1) / 2;
+	let offset = max_d + 1;
+	let length = 2 * max_d + 3;
+	let mut forward = vec![-1isize; length as usize];
+	let mut backward = vec![-1isize; length as usize];
+	forward[(offset + 1) as usize] = 0;
+	backward[(offset + 1) as usize] = 0;
+
+	let delta = n - m;
+	// with an odd delta the paths meet while searching forward
+	let meet_forward = delta % 2 != 0;
+	// diagonals that ran off the edges are skipped from then on
+	let (mut forward_start, mut forward_end) = (0, 0);
+	let (mut backward_start, mut backward_end) = (0, 0);
+
+	for d in 0..=max_d {
+		for k in (-d + forward_start..=d - forward_end).step_by(2) {
+			let index = (offset + k) as usize;
+			let mut x = match k == -d || (k != d && forward[index - 1] < forward[index + 1]) {
+				true => forward[index + 1],
+				false => forward[index - 1] + 1,
+			};
+			let mut y = x - k;
+			while x < n && y < m && old[x as usize] == new[y as usize] {
+				x += 1;
+				y += 1;
+			}
+			forward[index] = x;
+
+			if x > n {
+				forward_end += 2;
+			}
+			else if y > m {
+				forward_start += 2;
+			}
+			else if meet_forward {
+				let other = offset + delta - k;
+				if (0..length).contains(&other) && backward[other as usize] != -1 && x >= n - backward[other as usize] {
+					return Some((x as usize, y as usize))
+				}
+			}
+		}
+
+		for k in (-d + backward_start..=d - backward_end).step_by(2) {
+			let index = (offset + k) as usize;
+			let mut x = match k == -d || (k != d && backward[index - 1] < backward[index + 1]) {
+				true => backward[index + 1],
+				false => backward[index - 1] + 1,
+			};
+			let mut y = x - k;
+			while x < n && y < m && old[(n - x - 1) as usize] == new[(m - y - 1) as usize] {
+				x += 1;
+				y += 1;
+			}
+			backward[index] = x;
+
+			if x > n {
+				backward_end += 2;
+			}
+			else if y > m {
+				backward_start += 2;
+			}
+			else if !meet_forward {
+				let other = offset + delta - k;
+				if (0..length).contains(&other) && forward[other as usize] != -1 {
+					let forward_x = forward[other as usize];
+					let forward_y = offset + forward_x - other;
+					if forward_x >= n - x {
+						return Some((forward_x as usize, forward_y as usize))
+					}
+				}
+			}
+		}
+	}
+
+	None
+}
+
+// for every line of `old` the line of `new` it was kept as, if any
+fn matched_lines(old: &str, new: &str) -> Vec<Option<usize>> {
+	let mut matches = vec![];
+	let mut new_line = 0;
+	for line in History::diff(old, new) {
+		match line {
+			DiffLine::Equal(_) => {
+				matches.push(Some(new_line));
+				new_line += 1;
+			},
+			DiffLine::Added(_) => new_line += 1,
+			DiffLine::Removed(_) => matches.push(None),
+		}
+	}
+	matches
+}
+
+fn now() -> i64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|duration| duration.as_secs() as i64)
+		.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn apply(diff: &[DiffLine]) -> (Vec<&str>, Vec<&str>) {
+		let mut old = vec![];
+		let mut new = vec![];
+		for line in diff {
+			match line {
+				DiffLine::Equal(line) => {
+					old.push(line.as_str());
+					new.push(line.as_str());
+				},
+				DiffLine::Removed(line) => old.push(line.as_str()),
+				DiffLine::Added(line) => new.push(line.as_str()),
+			}
+		}
+		(old, new)
+	}
+
+	#[test]
+	fn diffs_added_and_removed_lines() {
+		assert_eq!(History::diff("one\ntwo", "one\ntwo"), vec![
+			DiffLine::Equal("one".to_string()),
+			DiffLine::Equal("two".to_string()),
+		]);
+		assert_eq!(History::diff("", "one"), vec![DiffLine::Added("one".to_string())]);
+		assert_eq!(History::diff("one", ""), vec![DiffLine::Removed("one".to_string())]);
+		assert_eq!(History::diff("one\ntwo\nthree", "one\n2\nthree\nfour"), vec![
+			DiffLine::Equal("one".to_string()),
+			DiffLine::Removed("two".to_string()),
+			DiffLine::Added("2".to_string()),
+			DiffLine::Equal("three".to_string()),
+			DiffLine::Added("four".to_string()),
+		]);
+	}
+
+	#[test]
+	fn diffs_keep_the_most_lines() {
+		let old = "a\nb\nc\na\nb\nb\na";
+		let new = "c\nb\na\nb\na\nc";
+		let diff = History::diff(old, new);
+		// the shortest edit script of this pair takes five edits
+		let edits = diff.iter().filter(|line| !matches!(line, DiffLine::Equal(_))).count();
+		assert_eq!(edits, 5);
+		assert_eq!(apply(&diff), (old.lines().collect(), new.lines().collect()));
+	}
+
+	#[test]
+	fn diffs_long_notes_that_share_nothing() {
+		let old: String = (0..5000).map(|line| format!("old {line}\n")).collect();
+		let new: String = (0..5000).map(|line| format!("new {line}\n")).collect();
+		let diff = History::diff(&old, &new);
+		assert_eq!(diff.len(), 10000);
+		assert_eq!(apply(&diff), (old.lines().collect(), new.lines().collect()));
+	}
+
+	#[test]
+	fn merges_changes_to_different_lines() {
+		let base = "one\ntwo\nthree\nfour\n";
+		let ours = "one\n2\nthree\nfour\n";
+		let theirs = "one\ntwo\nthree\n4\nfive\n";
+		assert_eq!(History::merge(base, ours, theirs).content, "one\n2\nthree\n4\nfive\n");
+	}
+
+	#[test]
+	fn merges_removed_lines() {
+		let base = "one\ntwo\nthree";
+		assert_eq!(History::merge(base, "one\nthree", "one\ntwo\nthree\nfour").content, "one\nthree\nfour");
+	}
+
+	#[test]
+	fn takes_the_same_change_once() {
+		let base = "one\ntwo";
+		assert_eq!(History::merge(base, "one\n2", "one\n2").content, "one\n2");
+	}
+
+	#[test]
+	fn keeps_both_versions_of_conflicting_lines() {
+		let base = "one\ntwo\nthree";
+		let merge = History::merge(base, "one\nours\nthree", "one\ntheirs\nthree");
+		assert_eq!(merge.content, "one\n<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\nthree");
+		assert_eq!(merge.conflicts, 1);
+	}
+
+	#[test]
+	fn keeps_removed_lines_that_were_added_to() {
+		let base = "one\ntwo\nthree";
+		let merge = History::merge(base, "one\nthree", "one\ntwo\nmore\nthree");
+		assert_eq!(merge.content, "one\n<<<<<<< ours\n=======\ntwo\nmore\n>>>>>>> theirs\nthree");
+		assert_eq!(merge.conflicts, 1);
+	}
+}
@@ -0,0 +1,339 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use walkdir::WalkDir;
+
+use crate::directories::Directories;
+use crate::notes::is_note;
+use crate::vault::{with_built, Vault};
+
+// every folder keeps the ids of its notes in a hidden sidecar file, one
+// `id name` pair per line. Ids don't change when a note is renamed or
+// moved, or when the whole notes directory is moved somewhere else
+const IDS_FILE: &str = ".bellbird-ids";
+
+static ID_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+// resolves ids to paths and back
+#[derive(Debug, Default)]
+pub struct IdIndex {
+	paths: HashMap<String, PathBuf>,
+	ids: HashMap<PathBuf, String>,
+}
+
+impl IdIndex {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	// reads the sidecar files below `roots`. Ids of notes that are gone are
+	// handed on to notes without an id, first to one with the same file name
+	// (moved by another program), then to the only one left in the same
+	// folder (renamed by another program)
+	pub fn build(roots: &[PathBuf]) -> Self {
+		let mut index = Self::new();
+		let mut orphans: Vec<(PathBuf, String, String)> = vec![];
+		let mut unknown_notes: Vec<PathBuf> = vec![];
+
+		for directory in roots.iter().flat_map(|root| directories(root)) {
+			for (id, name) in read_ids_file(&directory) {
+				let path = directory.join(&name);
+				match path.is_file() {
+					true => index.insert(&id, &path),
+					false => orphans.push((directory.clone(), id, name)),
+				}
+			}
+			unknown_notes.extend(notes(&directory).filter(|path| !index.ids.contains_key(path)));
+		}
+
+		if orphans.is_empty() {
+			return index
+		}
+
+		let mut changed_directories: HashSet<PathBuf> = HashSet::new();
+		orphans.retain(|(directory, id, name)| {
+			let candidates: Vec<&PathBuf> = unknown_notes
+				.iter()
+				.filter(|path| path.file_name().is_some_and(|candidate_name| *candidate_name == **name))
+				.collect();
+			if candidates.len() != 1 {
+				return true
+			}
+
+			let path = candidates[0].clone();
+			changed_directories.insert(directory.clone());
+			changed_directories.insert(parent(&path));
+			index.insert(id, &path);
+			unknown_notes.retain(|unknown_note| *unknown_note != path);
+			false
+		});
+
+		for (directory, id, _) in orphans.iter() {
+			changed_directories.insert(directory.clone());
+			let is_only_orphan = orphans.iter().filter(|(other, _, _)| other == directory).count() == 1;
+			let candidates: Vec<PathBuf> = unknown_notes
+				.iter()
+				.filter(|path| path.parent() == Some(directory.as_path()))
+				.cloned()
+				.collect();
+			if is_only_orphan && candidates.len() == 1 {
+				index.insert(id, &candidates[0]);
+			}
+		}
+
+		for directory in changed_directories {
+			if let Err(e) = write_ids_file(&directory, &index.entries(&directory)) {
+				eprintln!("Failed to write note ids: {:?}", e);
+			}
+		}
+		index
+	}
+
+	pub fn insert(&mut self, id: &str, path: &Path) {
+		self.paths.insert(id.to_string(), path.to_path_buf());
+		self.ids.insert(path.to_path_buf(), id.to_string());
+	}
+
+	pub fn remove(&mut self, path: &Path) -> Option<String> {
+		let id = self.ids.remove(path)?;
+		self.paths.remove(&id);
+		Some(id)
+	}
+
+	pub fn id(&self, path: &Path) -> Option<String> {
+		self.ids.get(path).cloned()
+	}
+
+	pub fn path(&self, id: &str) -> Option<PathBuf> {
+		self.paths.get(id).cloned()
+	}
+
+	pub fn reindex_directory(&mut self, directory: &Path) {
+		self.ids.retain(|path, _| !path.starts_with(directory));
+		self.paths.retain(|_, path| !path.starts_with(directory));
+		if directory.is_dir() {
+			let index = Self::build(&[directory.to_path_buf()]);
+			self.ids.extend(index.ids);
+			self.paths.extend(index.paths);
+		}
+	}
+
+	// the `(id, name)` pairs of the notes directly inside `directory`
+	fn entries(&self, directory: &Path) -> Vec<(String, String)> {
+		self.ids
+			.iter()
+			.filter(|(path, _)| path.parent() == Some(directory))
+			.map(|(path, id)| (id.clone(), file_name(path)))
+			.collect()
+	}
+}
+
+impl Vault {
+	// the id of a note, notes without one get a new id right away.
+	// `None` for anything that is not a note
+	pub fn note_id(&self, path: &Path) -> Option<String> {
+		let Ok(mut index) = self.id_index().lock() else {
+			return None
+		};
+		if let Some(id) = index.id(path) {
+			return Some(id)
+		}
+		if !is_note(path) {
+			return None
+		}
+
+		// notes outside the indexed roots still have their id in the sidecar file
+		let directory = parent(path);
+		let mut entries = read_ids_file(&directory);
+		let name = file_name(path);
+		if let Some((id, _)) = entries.iter().find(|(_, entry_name)| *entry_name == name) {
+			index.insert(id, path);
+			return Some(id.clone())
+		}
+
+		let id = new_id();
+		entries.push((id.clone(), name));
+		if let Err(e) = write_ids_file(&directory, &entries) {
+			eprintln!("Failed to write note id: {:?}", e);
+			return None
+		}
+		index.insert(&id, path);
+		Some(id)
+	}
+
+	pub fn note_path(&self, id: &str) -> Option<PathBuf> {
+		match self.id_index().lock() {
+			Ok(index) => index.path(id),
+			Err(_) => None
+		}
+	}
+
+	// the id moves along with the note, also in and out of the trash
+	pub(crate) fn move_note_id(&self, old_path: &Path, new_path: &Path) {
+		let Some(id) = remove_from_ids_file(old_path) else {
+			return
+		};
+		let directory = parent(new_path);
+		let mut entries = read_ids_file(&directory);
+		entries.retain(|(_, name)| *name != file_name(new_path));
+		entries.push((id.clone(), file_name(new_path)));
+		if let Err(e) = write_ids_file(&directory, &entries) {
+			eprintln!("Failed to write note id: {:?}", e);
+		}
+
+		with_built(&self.state().ids, |index| {
+			index.remove(old_path);
+			index.insert(&id, new_path);
+		});
+	}
+
+	// forgets the id of a deleted note and returns it
+	pub(crate) fn remove_note_id(&self, path: &Path) -> Option<String> {
+		let id = remove_from_ids_file(path);
+		with_built(&self.state().ids, |index| {
+			index.remove(path);
+		});
+		id
+	}
+
+	// the sidecar files move along with a folder, only the paths change
+	pub(crate) fn reindex_note_ids(&self, path: &Path) {
+		with_built(&self.state().ids, |index| index.reindex_directory(path));
+	}
+
+	// picks up notes that were moved or renamed by other programs
+	pub fn refresh_note_ids(&self) {
+		if self.state().ids.get().is_some() {
+			let fresh_index = IdIndex::build(&self.id_roots());
+			with_built(&self.state().ids, |index| *index = fresh_index);
+		}
+	}
+
+	fn id_index(&self) -> &Mutex<IdIndex> {
+		self.state().ids.get_or_init(|| Mutex::new(IdIndex::build(&self.id_roots())))
+	}
+
+	// trashed notes keep their ids so they are still known after a restore
+	fn id_roots(&self) -> Vec<PathBuf> {
+		let mut roots = vec![self.root().to_path_buf()];
+		if let Ok(trash_files) = self.trash_files_directory() {
+			roots.push(trash_files);
+		}
+		roots
+	}
+}
+
+#[derive(Debug)]
+pub struct NoteIds;
+
+impl NoteIds {
+	pub fn id(path: &Path) -> Option<String> {
+		Vault::current().note_id(path)
+	}
+
+	pub fn path(id: &str) -> Option<PathBuf> {
+		Vault::current().note_path(id)
+	}
+
+	pub fn refresh() {
+		Vault::current().refresh_note_ids();
+	}
+}
+
+// the ids in the sidecar files anywhere below `roots`, trash included,
+// without building an index, e.g. of notebooks that aren't open
+pub fn stored_ids(roots: &[PathBuf]) -> HashSet<String> {
+	roots
+		.iter()
+		.flat_map(|root| WalkDir::new(root).into_iter().flatten())
+		.filter(|entry| entry.file_type().is_dir())
+		.flat_map(|entry| read_ids_file(entry.path()))
+		.map(|(id, _)| id)
+		.collect()
+}
+
+// time based with a counter on top, unique enough without pulling in
+// a dependency for random numbers
+fn new_id() -> String {
+	let nanos = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|duration| duration.as_nanos())
+		.unwrap_or_default();
+	let counter = ID_COUNTER.fetch_add(1, Ordering::Relaxed) & 0xffff;
+	format!("{nanos:x}{counter:04x}")
+}
+
+fn read_ids_file(directory: &Path) -> Vec<(String, String)> {
+	let Ok(content) = fs::read_to_string(directory.join(IDS_FILE)) else {
+		return vec![]
+	};
+	content
+		.lines()
+		.filter_map(|line| line.split_once(' '))
+		.map(|(id, name)| (id.to_string(), name.to_string()))
+		.collect()
+}
+
+fn write_ids_file(directory: &Path, entries: &[(String, String)]) -> Result<()> {
+	let path = directory.join(IDS_FILE);
+	if entries.is_empty() {
+		if path.exists() {
+			fs::remove_file(path)?;
+		}
+		return Ok(())
+	}
+
+	let mut entries = entries.to_vec();
+	entries.sort_by(|a, b| a.1.cmp(&b.1));
+	let content: String = entries
+		.iter()
+		.map(|(id, name)| format!("{id} {name}\n"))
+		.collect();
+	fs::write(path, content)?;
+	Ok(())
+}
+
+fn remove_from_ids_file(path: &Path) -> Option<String> {
+	let directory = parent(path);
+	let name = file_name(path);
+	let mut entries = read_ids_file(&directory);
+	let position = entries.iter().position(|(_, entry_name)| *entry_name == name)?;
+	let (id, _) = entries.remove(position);
+	if let Err(e) = write_ids_file(&directory, &entries) {
+		eprintln!("Failed to write note ids: {:?}", e);
+	}
+	Some(id)
+}
+
+fn directories(root: &Path) -> impl Iterator<Item = PathBuf> {
+	WalkDir::new(root)
+		.into_iter()
+		.filter_entry(|e| e.depth() == 0 || !Directories::is_hidden(e))
+		.flatten()
+		.filter(|entry| entry.file_type().is_dir())
+		.map(|entry| entry.into_path())
+}
+
+fn notes(directory: &Path) -> impl Iterator<Item = PathBuf> {
+	fs::read_dir(directory)
+		.into_iter()
+		.flatten()
+		.flatten()
+		.map(|entry| entry.path())
+		.filter(|path| is_note(path))
+}
+
+fn parent(path: &Path) -> PathBuf {
+	path.parent().map(Path::to_path_buf).unwrap_or_default()
+}
+
+fn file_name(path: &Path) -> String {
+	path.file_name()
+		.map(|name| name.to_string_lossy().to_string())
+		.unwrap_or_default()
+}
@@ -0,0 +1,193 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::vault::Vault;
+
+const MAX_JOURNAL_SIZE: usize = 100;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+	Create { path: PathBuf, is_directory: bool },
+	Rename { old_path: PathBuf, new_path: PathBuf, is_directory: bool },
+	Delete { path: PathBuf, trashed_path: PathBuf },
+	Restore { trashed_path: PathBuf, path: PathBuf },
+	SetPinned { path: PathBuf, is_pinned: bool, was_pinned: bool },
+}
+
+impl Operation {
+	pub fn inverse(&self) -> Operation {
+		match self {
+			Operation::Create { path, .. } => Operation::Delete {
+				path: path.clone(),
+				trashed_path: PathBuf::new(),
+			},
+			Operation::Rename { old_path, new_path, is_directory } => Operation::Rename {
+				old_path: new_path.clone(),
+				new_path: old_path.clone(),
+				is_directory: *is_directory,
+			},
+			Operation::Delete { path, trashed_path } => Operation::Restore {
+				trashed_path: trashed_path.clone(),
+				path: path.clone(),
+			},
+			Operation::Restore { trashed_path, path } => Operation::Delete {
+				path: path.clone(),
+				trashed_path: trashed_path.clone(),
+			},
+			Operation::SetPinned { path, is_pinned, was_pinned } => Operation::SetPinned {
+				path: path.clone(),
+				is_pinned: *was_pinned,
+				was_pinned: *is_pinned,
+			},
+		}
+	}
+
+	pub fn description(&self) -> String {
+		let name = |path: &PathBuf| path.file_stem()
+			.map(|stem| stem.to_string_lossy().to_string())
+			.unwrap_or_default();
+
+		match self {
+			Operation::Create { path, .. } => format!("Create ´{}´", name(path)),
+			Operation::Rename { old_path, new_path, .. } => {
+				format!("Rename ´{}´ to ´{}´", name(old_path), name(new_path))
+			},
+			Operation::Delete { path, .. } => format!("Delete ´{}´", name(path)),
+			Operation::Restore { path, .. } => format!("Restore ´{}´", name(path)),
+			Operation::SetPinned { path, is_pinned: true, .. } => format!("Pin ´{}´", name(path)),
+			Operation::SetPinned { path, .. } => format!("Unpin ´{}´", name(path)),
+		}
+	}
+
+	// runs the operation and returns it with the paths it actually
+	// ended up using, e.g. the location an item got inside the trash
+	async fn apply(&self, vault: &Vault) -> Result<Operation> {
+		match self {
+			Operation::Create { path, is_directory: true } => {
+				vault.create_directory(path)?;
+			},
+			Operation::Create { path, is_directory: false } => {
+				vault.create_note(path)?;
+			},
+			Operation::Rename { old_path, new_path, is_directory: true } => {
+				vault.rename_directory(old_path, new_path)?;
+			},
+			Operation::Rename { old_path, new_path, is_directory: false } => {
+				vault.rename_note(old_path, new_path).await?;
+			},
+			Operation::Delete { path, .. } => {
+				let trashed_path = vault.move_to_trash(path)?;
+				return Ok(Operation::Delete { path: path.clone(), trashed_path })
+			},
+			Operation::Restore { trashed_path, path } => {
+				let path = vault.restore_trashed(trashed_path, path)?;
+				return Ok(Operation::Restore { trashed_path: trashed_path.clone(), path })
+			},
+			Operation::SetPinned { path, is_pinned, .. } => {
+				vault.set_is_pinned(path, *is_pinned)?;
+			},
+		}
+		Ok(self.clone())
+	}
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct JournalStacks {
+	undo: Vec<Operation>,
+	redo: Vec<Operation>,
+	// set while an undo or redo runs so the replayed file operations
+	// don't get recorded a second time
+	replaying: bool,
+}
+
+impl Vault {
+	pub(crate) fn record(&self, operation: Operation) {
+		if let Ok(mut journal) = self.state().journal.lock() {
+			if journal.replaying {
+				return
+			}
+			journal.undo.push(operation);
+			journal.redo.clear();
+
+			if journal.undo.len() > MAX_JOURNAL_SIZE {
+				journal.undo.remove(0);
+			}
+		}
+	}
+
+	pub fn can_undo(&self) -> bool {
+		self.state().journal.lock().map(|journal| !journal.undo.is_empty()).unwrap_or(false)
+	}
+
+	pub fn can_redo(&self) -> bool {
+		self.state().journal.lock().map(|journal| !journal.redo.is_empty()).unwrap_or(false)
+	}
+
+	// returns the operation that was run to undo the last one
+	pub async fn undo(&self) -> Result<Option<Operation>> {
+		let Some(operation) = self.pop_operation(|journal| &mut journal.undo) else {
+			return Ok(None)
+		};
+
+		let inverse = self.replay(&operation.inverse()).await?;
+		if let Ok(mut journal) = self.state().journal.lock() {
+			journal.redo.push(inverse.inverse());
+		}
+		Ok(Some(inverse))
+	}
+
+	pub async fn redo(&self) -> Result<Option<Operation>> {
+		let Some(operation) = self.pop_operation(|journal| &mut journal.redo) else {
+			return Ok(None)
+		};
+
+		let operation = self.replay(&operation).await?;
+		if let Ok(mut journal) = self.state().journal.lock() {
+			journal.undo.push(operation.clone());
+		}
+		Ok(Some(operation))
+	}
+
+	async fn replay(&self, operation: &Operation) -> Result<Operation> {
+		self.set_replaying(true);
+		let result = operation.apply(self).await;
+		self.set_replaying(false);
+		result
+	}
+
+	fn set_replaying(&self, is_replaying: bool) {
+		if let Ok(mut journal) = self.state().journal.lock() {
+			journal.replaying = is_replaying;
+		}
+	}
+
+	fn pop_operation<F>(&self, stack: F) -> Option<Operation>
+	where
+		F: FnOnce(&mut JournalStacks) -> &mut Vec<Operation>
+	{
+		self.state().journal.lock().ok().and_then(|mut journal| stack(&mut journal).pop())
+	}
+}
+
+// undo and redo of the file operations in `Vault::current`
+#[derive(Debug)]
+pub struct Journal;
+
+impl Journal {
+	pub fn can_undo() -> bool {
+		Vault::current().can_undo()
+	}
+
+	pub fn can_redo() -> bool {
+		Vault::current().can_redo()
+	}
+
+	pub async fn undo() -> Result<Option<Operation>> {
+		Vault::current().undo().await
+	}
+
+	pub async fn redo() -> Result<Option<Operation>> {
+		Vault::current().redo().await
+	}
+}
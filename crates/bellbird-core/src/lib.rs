@@ -1,3 +1,15 @@
 pub mod directories;
 pub mod notes;
 pub mod config;
+pub mod history;
+pub mod ids;
+pub mod journal;
+pub mod links;
+pub mod markdown;
+pub mod notebooks;
+pub mod search;
+pub mod sort;
+pub mod tags;
+pub mod trash;
+pub mod vault;
+pub mod watcher;
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::Result;
+use regex::{Captures, Regex};
+
+use crate::notes::{note_name, note_paths, NOTES_EXTENSION};
+use crate::vault::Vault;
+
+static WIKI_LINK: OnceLock<Regex> = OnceLock::new();
+
+// `[[Note Name]]`, `[[folder/Note Name]]` or `[[Note Name|shown text]]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct WikiLink {
+	pub target: String,
+	pub alias: Option<String>,
+	// char offsets of the whole link including the brackets
+	pub range: Range<usize>,
+}
+
+// which link targets every note contains, backlinks are looked up from here
+#[derive(Debug, Default)]
+pub struct LinkGraph {
+	outgoing: HashMap<PathBuf, Vec<String>>,
+}
+
+impl LinkGraph {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn build(root: &Path) -> Self {
+		let mut graph = Self::new();
+		for path in note_paths(root) {
+			if let Ok(content) = std::fs::read_to_string(&path) {
+				graph.insert(&path, &content);
+			}
+		}
+		graph
+	}
+
+	pub fn insert(&mut self, path: &Path, content: &str) {
+		let targets = Links::parse(content)
+			.into_iter()
+			.map(|link| link.target)
+			.collect();
+		self.outgoing.insert(path.to_path_buf(), targets);
+	}
+
+	pub fn remove(&mut self, path: &Path) {
+		self.outgoing.remove(path);
+	}
+
+	pub fn rename(&mut self, old_path: &Path, new_path: &Path) {
+		if let Some(targets) = self.outgoing.remove(old_path) {
+			self.outgoing.insert(new_path.to_path_buf(), targets);
+		}
+	}
+
+	pub fn reindex_directory(&mut self, directory: &Path) {
+		self.outgoing.retain(|path, _| !path.starts_with(directory));
+		if directory.is_dir() {
+			self.outgoing.extend(Self::build(directory).outgoing);
+		}
+	}
+
+	// every note linking to `path`, sorted by name
+	pub fn backlinks(&self, path: &Path) -> Vec<PathBuf> {
+		let mut backlinks: Vec<PathBuf> = self.outgoing
+			.iter()
+			.filter(|(source, _)| source.as_path() != path)
+			.filter(|(_, targets)| targets.iter().any(|target| target_matches(target, path)))
+			.map(|(source, _)| source.clone())
+			.collect();
+
+		backlinks.sort_by_key(|path| path.file_stem().map(|stem| stem.to_ascii_lowercase()));
+		backlinks
+	}
+}
+
+impl Vault {
+	pub fn backlinks(&self, path: &Path) -> Vec<PathBuf> {
+		match self.link_graph().lock() {
+			Ok(graph) => graph.backlinks(path),
+			Err(_) => vec![]
+		}
+	}
+
+	// the note a link target points to, notes next to `from` win over
+	// notes elsewhere and notes closer to the root over deeper ones
+	pub fn resolve_link(&self, target: &str, from: Option<&Path>) -> Option<PathBuf> {
+		let from_directory = from.and_then(|from| from.parent());
+
+		note_paths(self.root())
+			.filter(|path| target_matches(target, path))
+			.min_by_key(|path| (
+				path.parent() != from_directory,
+				path.components().count(),
+				path.clone()
+			))
+	}
+
+	// points every link to `old_path` at `new_path` instead, returns the
+	// notes that were changed
+	pub fn rewrite_links(&self, old_path: &Path, new_path: &Path) -> Result<Vec<PathBuf>> {
+		let mut rewritten = vec![];
+
+		for source in self.backlinks(old_path) {
+			let content = std::fs::read_to_string(&source)?;
+			let new_content = wiki_link().replace_all(&content, |captures: &Captures| {
+				let target = captures[1].trim();
+				if !target_matches(target, old_path) {
+					return captures[0].to_string()
+				}
+
+				let new_target = match target.contains('/') {
+					true => relative_target(self.root(), new_path),
+					false => note_name(new_path),
+				};
+				match captures.get(2) {
+					Some(alias) => format!("[[{}|{}]]", new_target, alias.as_str()),
+					None => format!("[[{}]]", new_target),
+				}
+			});
+
+			if new_content != content {
+				self.write_note(&source, &new_content)?;
+				rewritten.push(source);
+			}
+		}
+
+		Ok(rewritten)
+	}
+
+	fn link_graph(&self) -> &Mutex<LinkGraph> {
+		self.state().links.get_or_init(|| Mutex::new(LinkGraph::build(self.root())))
+	}
+}
+
+#[derive(Debug)]
+pub struct Links;
+
+impl Links {
+	pub fn parse(content: &str) -> Vec<WikiLink> {
+		let mut links = vec![];
+		// char offsets are counted along instead of from the start for every link
+		let (mut byte_offset, mut char_offset) = (0, 0);
+
+		for captures in wiki_link().captures_iter(content) {
+			let whole = captures.get(0).unwrap();
+			char_offset += content[byte_offset..whole.start()].chars().count();
+			let length = whole.as_str().chars().count();
+			byte_offset = whole.end();
+
+			links.push(WikiLink {
+				target: captures[1].trim().to_string(),
+				alias: captures.get(2).map(|alias| alias.as_str().trim().to_string()),
+				range: char_offset..char_offset + length,
+			});
+			char_offset += length;
+		}
+
+		links
+	}
+
+	pub fn resolve(target: &str, from: Option<&Path>) -> Option<PathBuf> {
+		Vault::current().resolve_link(target, from)
+	}
+
+	pub fn backlinks(path: &Path) -> Vec<PathBuf> {
+		Vault::current().backlinks(path)
+	}
+
+	pub fn rewrite_links(old_path: &Path, new_path: &Path) -> Result<Vec<PathBuf>> {
+		Vault::current().rewrite_links(old_path, new_path)
+	}
+}
+
+fn wiki_link() -> &'static Regex {
+	WIKI_LINK.get_or_init(|| Regex::new(r"\[\[([^\[\]|\n]+)(?:\|([^\[\]\n]+))?\]\]").unwrap())
+}
+
+// `[[Note]]` matches every note called `Note`, `[[folder/Note]]` only
+// the ones that are inside a folder called `folder`
+fn target_matches(target: &str, path: &Path) -> bool {
+	let target = target.trim();
+	let target = target.strip_suffix(&format!(".{NOTES_EXTENSION}")).unwrap_or(target);
+	let target_components: Vec<&str> = target
+		.split('/')
+		.map(str::trim)
+		.filter(|component| !component.is_empty())
+		.collect();
+	if target_components.is_empty() {
+		return false
+	}
+
+	let path = path.with_extension("");
+	let mut path_components = path
+		.components()
+		.rev()
+		.map(|component| component.as_os_str().to_string_lossy().to_lowercase());
+
+	target_components.iter().rev().all(|target_component| {
+		path_components.next().is_some_and(|component| component == target_component.to_lowercase())
+	})
+}
+
+fn relative_target(root: &Path, path: &Path) -> String {
+	let path = path.with_extension("");
+	path.strip_prefix(root)
+		.map(Path::to_path_buf)
+		.unwrap_or(path)
+		.display()
+		.to_string()
+}
@@ -0,0 +1,190 @@
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarkdownStyle {
+	Heading(u8),
+	Strong,
+	Emphasis,
+	Strikethrough,
+	Code,
+	CodeBlock,
+	Link(String),
+	Quote,
+}
+
+// a piece of text and every style that applies to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownSpan {
+	pub text: String,
+	pub styles: Vec<MarkdownStyle>,
+}
+
+#[derive(Debug)]
+pub struct Markdown;
+
+impl Markdown {
+	// turns a note into styled spans of plain text, how the styles
+	// look is up to whoever displays them
+	pub fn render(content: &str) -> Vec<MarkdownSpan> {
+		let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+		let mut renderer = Renderer::default();
+
+		for event in Parser::new_ext(content, options) {
+			renderer.event(event);
+		}
+
+		renderer.spans
+	}
+}
+
+#[derive(Debug, Default)]
+struct Renderer {
+	spans: Vec<MarkdownSpan>,
+	styles: Vec<MarkdownStyle>,
+	// the next number of every list we are in, `None` for bullet lists
+	lists: Vec<Option<u64>>,
+	// right after a list marker, where paragraphs must not break the line
+	at_item_start: bool,
+}
+
+impl Renderer {
+	fn event(&mut self, event: Event) {
+		match event {
+			Event::Start(tag) => self.start(tag),
+			Event::End(tag_end) => self.end(tag_end),
+			Event::Text(text) => self.push(&text),
+			Event::Code(code) => {
+				self.styles.push(MarkdownStyle::Code);
+				self.push(&code);
+				self.styles.pop();
+			},
+			Event::SoftBreak => self.push(" "),
+			Event::HardBreak => self.push("\n"),
+			Event::Rule => {
+				self.block_break();
+				self.push("―――\n\n");
+			},
+			Event::TaskListMarker(checked) => {
+				self.push(if checked { "☑ " } else { "☐ " });
+			},
+			Event::Html(html) | Event::InlineHtml(html) => self.push(&html),
+			_ => {}
+		}
+	}
+
+	fn start(&mut self, tag: Tag) {
+		let at_item_start = self.at_item_start;
+		self.at_item_start = false;
+
+		match tag {
+			Tag::Heading { level, .. } => {
+				self.block_break();
+				self.styles.push(MarkdownStyle::Heading(heading_level(level)));
+			},
+			Tag::Paragraph if at_item_start => {},
+			Tag::Paragraph => self.block_break(),
+			Tag::BlockQuote(_) => {
+				self.block_break();
+				self.styles.push(MarkdownStyle::Quote);
+			},
+			Tag::CodeBlock(_) => {
+				self.block_break();
+				self.styles.push(MarkdownStyle::CodeBlock);
+			},
+			Tag::List(first_number) => {
+				if self.lists.is_empty() {
+					self.block_break();
+				}
+				self.lists.push(first_number);
+			},
+			Tag::Item => {
+				self.line_break();
+				let indent = "    ".repeat(self.lists.len().saturating_sub(1));
+				let marker = match self.lists.last_mut() {
+					Some(Some(number)) => {
+						*number += 1;
+						format!("{}. ", *number - 1)
+					},
+					_ => "• ".to_string()
+				};
+				self.push(&format!("{indent}{marker}"));
+				self.at_item_start = true;
+			},
+			Tag::Emphasis => self.styles.push(MarkdownStyle::Emphasis),
+			Tag::Strong => self.styles.push(MarkdownStyle::Strong),
+			Tag::Strikethrough => self.styles.push(MarkdownStyle::Strikethrough),
+			Tag::Link { dest_url, .. } => {
+				self.styles.push(MarkdownStyle::Link(dest_url.to_string()));
+			},
+			_ => {}
+		}
+	}
+
+	fn end(&mut self, tag_end: TagEnd) {
+		match tag_end {
+			TagEnd::Heading(_) | TagEnd::BlockQuote(_) | TagEnd::CodeBlock => {
+				self.styles.pop();
+				self.line_break();
+			},
+			TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Link => {
+				self.styles.pop();
+			},
+			TagEnd::List(_) => {
+				self.lists.pop();
+			},
+			TagEnd::Paragraph => self.line_break(),
+			_ => {}
+		}
+	}
+
+	fn push(&mut self, text: &str) {
+		if text.is_empty() {
+			return
+		}
+		self.at_item_start = false;
+
+		match self.spans.last_mut() {
+			Some(span) if span.styles == self.styles => span.text.push_str(text),
+			_ => self.spans.push(MarkdownSpan {
+				text: text.to_string(),
+				styles: self.styles.clone(),
+			})
+		}
+	}
+
+	fn text_ends_with(&self, suffix: &str) -> bool {
+		self.spans.last().map_or(true, |span| span.text.ends_with(suffix))
+	}
+
+	fn line_break(&mut self) {
+		if !self.text_ends_with("\n") {
+			self.push_unstyled("\n");
+		}
+	}
+
+	// blocks are separated by an empty line
+	fn block_break(&mut self) {
+		if self.spans.is_empty() || self.text_ends_with("\n\n") {
+			return
+		}
+		self.line_break();
+		self.push_unstyled("\n");
+	}
+
+	fn push_unstyled(&mut self, text: &str) {
+		let styles = std::mem::take(&mut self.styles);
+		self.push(text);
+		self.styles = styles;
+	}
+}
+
+fn heading_level(level: HeadingLevel) -> u8 {
+	match level {
+		HeadingLevel::H1 => 1,
+		HeadingLevel::H2 => 2,
+		HeadingLevel::H3 => 3,
+		HeadingLevel::H4 => 4,
+		HeadingLevel::H5 => 5,
+		HeadingLevel::H6 => 6,
+	}
+}
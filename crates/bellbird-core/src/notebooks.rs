@@ -0,0 +1,160 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::config::{Config, ConfigOptions, ConfigSections, Settings};
+use crate::directories::Directories;
+use crate::ids;
+use crate::vault::Vault;
+
+// every notebook has a section of its own in the config file. The root of
+// the active notebook is the notes directory in the settings, its current
+// folder, note and tabs are the ones in the `General` section
+const SECTION_PREFIX: &str = "Notebook ";
+// the name of the notebook set up before there were notebooks
+const DEFAULT_NOTEBOOK_NAME: &str = "Notes";
+// what every notebook remembers on its own
+const STATE_OPTIONS: [ConfigOptions; 3] = [
+	ConfigOptions::CurrentDirectory,
+	ConfigOptions::CurrentNote,
+	ConfigOptions::OpenNotes,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notebook {
+	pub name: String,
+	// relative to the home directory unless it is absolute
+	pub directory: PathBuf,
+}
+
+impl Notebook {
+	pub fn root(&self) -> Option<PathBuf> {
+		Directories::notes_root(&self.directory)
+	}
+}
+
+#[derive(Debug)]
+pub struct Notebooks;
+
+impl Notebooks {
+	// sorted by name, the active notebook is always part of it
+	pub fn list() -> Vec<Notebook> {
+		let active = Self::active();
+		let mut config = Config::new();
+		let mut notebooks: Vec<Notebook> = config
+			.config_sections()
+			.iter()
+			.filter_map(|section| section.strip_prefix(SECTION_PREFIX))
+			.filter(|name| *name != active.name)
+			.map(|name| Notebook {
+				name: name.to_string(),
+				directory: config
+					.config_value(&section(name), ConfigOptions::UserNotesDirectory)
+					.map(PathBuf::from)
+					.unwrap_or_default(),
+			})
+			.collect();
+
+		notebooks.push(active);
+		notebooks.sort_by_key(|notebook| notebook.name.to_lowercase());
+		notebooks
+	}
+
+	// drops the metainfos of the active notebook's notes and folders that
+	// are gone, the ones of other notebooks are kept
+	pub fn collect_stale_meta_infos() -> Result<usize> {
+		let vault = Vault::current();
+		let other_roots: Vec<PathBuf> = Self::list()
+			.iter()
+			.filter_map(Notebook::root)
+			.filter(|root| root != vault.root())
+			.collect();
+		vault.config().collect_stale_meta_infos(&vault, &ids::stored_ids(&other_roots))
+	}
+
+	pub fn active() -> Notebook {
+		let name = Config::new()
+			.config_value(ConfigSections::General.as_str(), ConfigOptions::ActiveNotebook)
+			.filter(|name| !name.trim().is_empty())
+			.unwrap_or(DEFAULT_NOTEBOOK_NAME.to_string());
+
+		Notebook {
+			name,
+			directory: Settings::get().notes_directory,
+		}
+	}
+
+	pub fn add(name: &str, directory: &Path) -> Result<Notebook> {
+		let name = name.trim();
+		if name.is_empty() {
+			return Err(anyhow::anyhow!("The notebook needs a name"))
+		}
+		if directory.as_os_str().is_empty() {
+			return Err(anyhow::anyhow!("The notebook needs a folder"))
+		}
+		if Self::list().iter().any(|notebook| notebook.name == name) {
+			return Err(anyhow::anyhow!("There already is a notebook called ´{name}´"))
+		}
+
+		Config::new().set_config_value(
+			&section(name),
+			ConfigOptions::UserNotesDirectory,
+			directory.display().to_string()
+		)?;
+		Ok(Notebook {
+			name: name.to_string(),
+			directory: directory.to_path_buf(),
+		})
+	}
+
+	// only forgets about the notebook, its notes stay where they are
+	pub fn remove(name: &str) -> Result<()> {
+		if Self::active().name == name {
+			return Err(anyhow::anyhow!("The open notebook can not be removed"))
+		}
+		Config::new().remove_config_section(&section(name))
+	}
+
+	// the active notebook keeps its current folder, note and tabs in its
+	// section and the ones of `name` take their place. The indexes still
+	// have to follow, see `Directories::root_changed`
+	pub fn switch_to(name: &str) -> Result<Notebook> {
+		let active = Self::active();
+		if active.name == name {
+			return Ok(active)
+		}
+		let Some(notebook) = Self::list().into_iter().find(|notebook| notebook.name == name) else {
+			return Err(anyhow::anyhow!("There is no notebook called ´{name}´"))
+		};
+
+		let mut config = Config::new();
+		let general = ConfigSections::General.as_str();
+		let active_section = section(&active.name);
+		let notebook_section = section(&notebook.name);
+
+		config.set_config_value(
+			&active_section,
+			ConfigOptions::UserNotesDirectory,
+			active.directory.display().to_string()
+		)?;
+		for option in STATE_OPTIONS {
+			let value = config.config_value(general, option).unwrap_or_default();
+			config.set_config_value(&active_section, option, value)?;
+		}
+
+		let mut settings = Settings::get();
+		settings.notes_directory = notebook.directory.clone();
+		settings.save()?;
+
+		for option in STATE_OPTIONS {
+			let value = config.config_value(&notebook_section, option).unwrap_or_default();
+			config.set_config_value(general, option, value)?;
+		}
+		config.set_config_value(general, ConfigOptions::ActiveNotebook, notebook.name.clone())?;
+		Ok(notebook)
+	}
+}
+
+fn section(name: &str) -> String {
+	format!("{SECTION_PREFIX}{name}")
+}
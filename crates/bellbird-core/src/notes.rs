@@ -1,29 +1,49 @@
 use async_std::{fs, stream::StreamExt};
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 use anyhow::Result;
+use walkdir::WalkDir;
 
 use crate::config::{Config, ConfigOptions, ConfigSections};
+use crate::directories::{copy_path, Directories};
+use crate::journal::Operation;
+use crate::sort::SortMode;
+use crate::vault::Vault;
+use crate::watcher::Watcher;
 
-const NOTES_EXTENSION: &str = "note";
+pub const NOTES_EXTENSION: &str = "note";
+const PREVIEW_LINES: usize = 2;
+const PREVIEW_LENGTH: usize = 120;
+// the open notes are stored on a single line of the config file
+const OPEN_NOTES_SEPARATOR: &str = "|";
 
 #[derive(Debug, Clone)]
 pub struct Note {
-	// pub id: i32,
+	// see `NoteIds`
+	pub id: String,
 	pub name: String,
 	pub	path: String,
-	// pub	preview: String,
 	pub is_pinned: bool,
 	// pub is_current: bool,
 	// pub is_loaded: bool,
-	// pub creation_date: String,
-	// pub file_size: u64
+	pub metadata: NoteMetadata,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NoteMetadata {
+	// unix timestamps in seconds
+	pub created: Option<i64>,
+	pub modified: Option<i64>,
+	pub file_size: u64,
+	pub word_count: usize,
+	pub preview: String,
 }
 
 #[derive(Debug)]
 pub struct Notes;
 
-impl<'a> Notes {
-	pub async fn list(path: &Path) -> Result<Vec<Note>> {
+impl Vault {
+	pub async fn notes(&self, path: &Path) -> Result<Vec<Note>> {
 		if !path.exists() {
 			return Err(anyhow::anyhow!(
 				"Could not list file, path does not exist: {}",
@@ -31,7 +51,7 @@ impl<'a> Notes {
 			));
 		}
 
-		let pinned_notes = Notes::pinned_notes(path).unwrap();
+		let pinned_notes = self.pinned_notes(path);
 		let mut notes: Vec<Note> = vec![];
 
 		if let Ok(mut paths) = fs::read_dir(path).await {
@@ -40,51 +60,264 @@ impl<'a> Notes {
 					if dir_entry.path().is_dir().await {
 						continue
 					}
-
-					let file_path = dir_entry.path().display().to_string();
-					let mut file_name = dir_entry.file_name();
-
-					let mut is_pinned = false;
-					if pinned_notes.contains(&file_path) {
-						is_pinned = true;
+					// sidecar and temp files
+					if dir_entry.file_name().to_string_lossy().starts_with('.') {
+						continue
 					}
 
-					file_name = Path::new(&file_name).with_extension("").into();
-
-					notes.push(Note {
-						name: file_name.to_str().unwrap().to_string(),
-						path: file_path.clone(),
-						is_pinned
-					});
+					let file_path = PathBuf::from(dir_entry.path().as_os_str());
+					let is_pinned = pinned_notes.contains(&file_path);
+					notes.push(self.note(&file_path, is_pinned).await);
 				}
 			}
 		}
 
-		notes.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+		self.sort_mode(path).sort(&mut notes);
 		Ok(notes)
 	}
 
-	pub fn write_to_file(mut path: PathBuf, content: String) -> Result<()> {
-		path = Self::ensure_correct_path(&path);
-		let _ = std::fs::write(path, content);
+	pub fn create_note(&self, path: &Path) -> Result<PathBuf> {
+		let path = Notes::ensure_correct_path(path);
+		if path.exists() {
+			return Err(anyhow::anyhow!(
+				"Could not create note, file already exists: {}",
+				path.display()
+			));
+		}
+
+		std::fs::write(&path, "")?;
+		self.index_note(&path, "");
+		self.record(Operation::Create { path: path.clone(), is_directory: false });
+		Ok(path)
+	}
+
+	// copies the note next to the original
+	pub fn duplicate_note(&self, path: &Path) -> Result<PathBuf> {
+		let path = Notes::ensure_correct_path(path);
+		let new_path = copy_path(&path);
+		let content = std::fs::read_to_string(&path)?;
+		std::fs::write(&new_path, &content)?;
+
+		let _ = self.config().copy_meta_infos(
+			&self.meta_info_section(&path),
+			&self.meta_info_section(&new_path)
+		);
+		self.index_note(&new_path, &content);
+		self.record(Operation::Create { path: new_path.clone(), is_directory: false });
+		Ok(new_path)
+	}
+
+	pub fn write_note(&self, path: &Path, content: &str) -> Result<()> {
+		let path = Notes::ensure_correct_path(path);
+		write_atomically(&path, content.as_bytes())
+			.map_err(|e| anyhow::anyhow!("Could not save ´{}´: {}", path.display(), e))?;
+
+		Watcher::acknowledge(&path);
+		self.index_note(&path, content);
+		if let Err(e) = self.snapshot(&path, content) {
+			eprintln!("Failed to store note revision: {:?}", e);
+		}
 		Ok(())
 	}
 
-	pub async fn rename(mut old_path: PathBuf, mut new_path: PathBuf) -> Result<bool> {
-		old_path = Self::ensure_correct_path(&old_path);
-		new_path = Self::ensure_correct_path(&new_path);
+	pub async fn rename_note(&self, old_path: &Path, new_path: &Path) -> Result<()> {
+		let old_path = Notes::ensure_correct_path(old_path);
+		let new_path = Notes::ensure_correct_path(new_path);
+		// the note of that name would be gone for good, e.g. when an undo
+		// brings back a name that has been taken again in the meantime
+		if new_path != old_path && new_path.exists() {
+			return Err(anyhow::anyhow!(
+				"Could not rename note, ´{}´ already exists",
+				new_path.display()
+			));
+		}
 
-		return match fs::rename(old_path, new_path).await {
-			Ok(()) => Ok(true),
+		match fs::rename(&old_path, &new_path).await {
+			Ok(()) => {
+				// metainfos and history are keyed by id and stay put
+				self.move_note_id(&old_path, &new_path);
+				self.index_renamed_note(&old_path, &new_path);
+				self.record(Operation::Rename { old_path, new_path, is_directory: false });
+				Ok(())
+			},
 			Err(e) => Err(anyhow::anyhow!("Could not rename file: {}", e))
 		}
 	}
 
-	pub async fn delete(path: &Path) -> bool {
-		match fs::remove_file(path).await {
-			Ok(_) => true,
-			Err(_e) => false,
+	// moves the note into another folder, pin state and caret position move along
+	pub async fn move_note(&self, path: &Path, directory: &Path) -> Result<PathBuf> {
+		let path = Notes::ensure_correct_path(path);
+		let Some(file_name) = path.file_name() else {
+			return Err(anyhow::anyhow!("Could not move note, invalid path: {}", path.display()));
+		};
+
+		let new_path = directory.join(file_name);
+		if new_path == path {
+			return Ok(new_path)
+		}
+		if new_path.exists() {
+			return Err(anyhow::anyhow!(
+				"Could not move note, ´{}´ already exists in ´{}´",
+				file_name.to_string_lossy(),
+				directory.display()
+			));
 		}
+
+		self.rename_note(&path, &new_path).await?;
+		Ok(new_path)
+	}
+
+	// notes are moved to the trash, see `Vault::delete_permanently`
+	pub fn delete_note(&self, path: &Path) -> Result<PathBuf> {
+		let trashed_path = self.move_to_trash(path)?;
+		self.record(Operation::Delete { path: path.to_path_buf(), trashed_path: trashed_path.clone() });
+		Ok(trashed_path)
+	}
+
+	// works for notes and folders alike
+	pub fn is_pinned(&self, path: &Path) -> bool {
+		let config = self.config();
+		match config.meta_info(&self.meta_info_section(path), ConfigOptions::Pinned) {
+			Some(value) => value == "true",
+			None => false
+		}
+	}
+
+	pub fn set_is_pinned(&self, path: &Path, is_pinned: bool) -> Result<()> {
+		self.record(Operation::SetPinned {
+			path: path.to_path_buf(),
+			is_pinned,
+			was_pinned: self.is_pinned(path),
+		});
+
+		let is_pinned = if is_pinned == true { "true" } else { "false" };
+		self.config().set_meta_value(
+			&self.meta_info_section(path),
+			ConfigOptions::Pinned,
+			is_pinned.to_string()
+		)
+	}
+
+	// the pinned notes inside `directory`
+	pub fn pinned_notes(&self, directory: &Path) -> Vec<PathBuf> {
+		let mut config = self.config();
+		let sections = config.sections_by_value(ConfigOptions::Pinned, "true".to_string());
+
+		// pinned folders share the option, only notes of this very folder count
+		sections
+			.unwrap_or_default()
+			.iter()
+			.map(|section| self.note_path(section).unwrap_or(PathBuf::from(section)))
+			.filter(|path| path.parent() == Some(directory) && !path.is_dir())
+			.collect()
+	}
+
+	// the metainfos of notes are kept under their id so they survive
+	// renames, folders still use their path
+	pub fn meta_info_section(&self, path: &Path) -> String {
+		self.note_id(path).unwrap_or_else(|| path.display().to_string())
+	}
+
+	// tagged notes from every folder, see `Tags::parse`
+	pub async fn notes_by_tag(&self, tag: &str) -> Result<Vec<Note>> {
+		let mut notes: Vec<Note> = vec![];
+		for path in self.tagged_notes(tag) {
+			let is_pinned = self.is_pinned(&path);
+			notes.push(self.note(&path, is_pinned).await);
+		}
+
+		SortMode::default().sort(&mut notes);
+		Ok(notes)
+	}
+
+	async fn note(&self, path: &Path, is_pinned: bool) -> Note {
+		let name = path.with_extension("")
+			.file_name()
+			.map(|name| name.to_string_lossy().to_string())
+			.unwrap_or_default();
+
+		Note {
+			id: self.note_id(path).unwrap_or_default(),
+			name,
+			path: path.display().to_string(),
+			is_pinned,
+			metadata: Notes::metadata(path).await,
+		}
+	}
+}
+
+impl<'a> Notes {
+	pub async fn list(path: &Path) -> Result<Vec<Note>> {
+		Vault::current().notes(path).await
+	}
+
+	pub async fn list_by_tag(tag: &str) -> Result<Vec<Note>> {
+		Vault::current().notes_by_tag(tag).await
+	}
+
+	pub async fn metadata(path: &Path) -> NoteMetadata {
+		let mut metadata = NoteMetadata::default();
+
+		if let Ok(file_metadata) = fs::metadata(path).await {
+			metadata.created = file_metadata.created().ok().and_then(unix_timestamp);
+			metadata.modified = file_metadata.modified().ok().and_then(unix_timestamp);
+			metadata.file_size = file_metadata.len();
+		}
+
+		if let Ok(content) = fs::read_to_string(path).await {
+			metadata.word_count = content.split_whitespace().count();
+			metadata.preview = Self::preview(&content);
+		}
+
+		metadata
+	}
+
+	fn preview(content: &str) -> String {
+		let preview = content
+			.lines()
+			.map(|line| line.trim())
+			.filter(|line| !line.is_empty())
+			.take(PREVIEW_LINES)
+			.collect::<Vec<&str>>()
+			.join(" ");
+
+		match preview.char_indices().nth(PREVIEW_LENGTH) {
+			Some((index, _)) => format!("{}…", &preview[..index]),
+			None => preview
+		}
+	}
+
+	pub fn sort_mode(path: &Path) -> SortMode {
+		SortMode::for_directory(path)
+	}
+
+	pub fn set_sort_mode(path: &Path, sort_mode: SortMode) {
+		sort_mode.set_for_directory(path);
+	}
+
+	pub fn create(path: &Path) -> Result<PathBuf> {
+		Vault::current().create_note(path)
+	}
+
+	pub fn duplicate(path: &Path) -> Result<PathBuf> {
+		Vault::current().duplicate_note(path)
+	}
+
+	pub fn write_to_file(path: PathBuf, content: String) -> Result<()> {
+		Vault::current().write_note(&path, &content)
+	}
+
+	pub async fn rename(old_path: PathBuf, new_path: PathBuf) -> Result<bool> {
+		Vault::current().rename_note(&old_path, &new_path).await?;
+		Ok(true)
+	}
+
+	pub async fn move_to(path: &Path, directory: &Path) -> Result<PathBuf> {
+		Vault::current().move_note(path, directory).await
+	}
+
+	pub async fn delete(path: &Path) -> bool {
+		Vault::current().delete_note(path).is_ok()
 	}
 
 	pub fn current_path() -> Option<PathBuf> {
@@ -109,7 +342,34 @@ impl<'a> Notes {
 		);
 	}
 
-	fn ensure_correct_path(path: &Path) -> PathBuf {
+	// the notes open in the editor's tabs, in tab order
+	pub fn open_paths() -> Vec<PathBuf> {
+		Config::new()
+			.config_value(ConfigSections::General.as_str(), ConfigOptions::OpenNotes)
+			.map(|value| {
+				value.split(OPEN_NOTES_SEPARATOR)
+					.filter(|path| !path.is_empty())
+					.map(PathBuf::from)
+					.collect()
+			})
+			.unwrap_or_default()
+	}
+
+	pub fn set_open_paths(paths: &[PathBuf]) {
+		let value = paths
+			.iter()
+			.map(|path| path.display().to_string())
+			.collect::<Vec<String>>()
+			.join(OPEN_NOTES_SEPARATOR);
+
+		let _ = Config::new().set_config_value(
+			ConfigSections::General.as_str(),
+			ConfigOptions::OpenNotes,
+			value
+		);
+	}
+
+	pub fn ensure_correct_path(path: &Path) -> PathBuf {
 		match path.extension() {
 			Some(_) => path.to_path_buf(),
 			None => {
@@ -123,36 +383,74 @@ impl<'a> Notes {
 		}
 	}
 
+	pub fn meta_info_section(path: &Path) -> String {
+		Vault::current().meta_info_section(path)
+	}
+
 	pub fn is_pinned(path: &std::path::Path) -> bool {
-		let config = Config::new();
-		let path = path.display().to_string();
-		match config.meta_info(&path, ConfigOptions::Pinned) {
-			Some(value) => value == "true",
-			None => false
-		}
+		Vault::current().is_pinned(path)
 	}
 
 	pub fn set_is_pinned(path: &Path, is_pinned: bool) {
-		let is_pinned = if is_pinned == true { "true" } else { "false" };
-		let _  = Config::new().set_meta_value(
-			&path.display().to_string(),
-			ConfigOptions::Pinned,
-			is_pinned.to_string()
-		);
+		let _ = Vault::current().set_is_pinned(path, is_pinned);
 	}
 
 	pub fn pinned_notes(path: &Path) -> Option<Vec<String>> {
-		let mut config = Config::new();
-		let sections = config.sections_by_value(ConfigOptions::Pinned, "true".to_string());
-		let path_str = path.to_str().unwrap();
-		let mut pinned_notes = vec![];
+		let pinned_notes = Vault::current()
+			.pinned_notes(path)
+			.iter()
+			.map(|path| path.display().to_string())
+			.collect();
+		Some(pinned_notes)
+	}
+}
 
-		sections.clone().unwrap().iter().for_each(|section| {
-			if section.contains(path_str) {
-				pinned_notes.push(section.to_string());
-			}
-		});
+// writes into a hidden temp file next to the note first and renames it
+// over the note afterwards, so a failed write never leaves a truncated note
+fn write_atomically(path: &Path, content: &[u8]) -> std::io::Result<()> {
+	use std::io::Write;
 
-		Some(pinned_notes)
+	let file_name = path.file_name()
+		.map(|name| name.to_string_lossy().to_string())
+		.unwrap_or_default();
+	let temp_path = path.with_file_name(format!(".{file_name}.tmp"));
+
+	let result = std::fs::File::create(&temp_path)
+		.and_then(|mut file| {
+			file.write_all(content)?;
+			file.sync_all()
+		})
+		.and_then(|_| std::fs::rename(&temp_path, path));
+
+	if result.is_err() {
+		let _ = std::fs::remove_file(&temp_path);
 	}
+	result
+}
+
+pub(crate) fn is_note(path: &Path) -> bool {
+	path.is_file() && path.extension().is_some_and(|ext| ext == NOTES_EXTENSION)
+}
+
+pub(crate) fn note_name(path: &Path) -> String {
+	path.file_stem()
+		.map(|stem| stem.to_string_lossy().to_string())
+		.unwrap_or_default()
+}
+
+// every note below `root`, hidden files and folders are skipped
+pub(crate) fn note_paths(root: &Path) -> impl Iterator<Item = PathBuf> {
+	WalkDir::new(root)
+		.min_depth(1)
+		.into_iter()
+		.filter_entry(|e| !Directories::is_hidden(e))
+		.flatten()
+		.map(|entry| entry.into_path())
+		.filter(|path| is_note(path))
+}
+
+fn unix_timestamp(time: SystemTime) -> Option<i64> {
+	time.duration_since(UNIX_EPOCH)
+		.ok()
+		.map(|duration| duration.as_secs() as i64)
 }
@@ -0,0 +1,727 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::notes::{note_name, note_paths};
+use crate::vault::Vault;
+
+// BM25 tuning parameters
+const K1: f32 = 1.2;
+const B: f32 = 0.75;
+// extra weight for terms that also appear in the note name
+const NAME_BOOST: f32 = 2.0;
+const SNIPPET_CONTEXT: usize = 40;
+const MAX_SNIPPETS: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+	pub name: String,
+	pub path: PathBuf,
+	pub score: f32,
+	pub snippets: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+	Term(String),
+	Prefix(String),
+	Phrase(Vec<String>),
+	And(Vec<Query>),
+	Or(Vec<Query>),
+	Not(Box<Query>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+	term: String,
+	range: Range<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct Document {
+	name: String,
+	path: PathBuf,
+	content: String,
+	name_terms: HashSet<String>,
+	length: usize,
+}
+
+type DocId = usize;
+
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+	documents: HashMap<DocId, Document>,
+	ids: HashMap<PathBuf, DocId>,
+	// term -> document -> token positions
+	postings: BTreeMap<String, HashMap<DocId, Vec<usize>>>,
+	// term -> documents whose note name contains it
+	name_postings: BTreeMap<String, HashSet<DocId>>,
+	next_id: DocId,
+	total_length: usize,
+}
+
+impl SearchIndex {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn build(root: &Path) -> Self {
+		let mut index = Self::new();
+		for path in note_paths(root) {
+			if let Ok(content) = std::fs::read_to_string(&path) {
+				index.insert(&path, &content);
+			}
+		}
+
+		index
+	}
+
+	pub fn len(&self) -> usize {
+		self.documents.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.documents.is_empty()
+	}
+
+	pub fn insert(&mut self, path: &Path, content: &str) {
+		self.remove(path);
+
+		let id = self.next_id;
+		self.next_id += 1;
+
+		let tokens = tokenize(content);
+		for (position, token) in tokens.iter().enumerate() {
+			self.postings
+				.entry(token.term.clone())
+				.or_default()
+				.entry(id)
+				.or_default()
+				.push(position);
+		}
+
+		let name = note_name(path);
+		let name_terms: HashSet<String> = tokenize(&name).into_iter().map(|t| t.term).collect();
+		for term in name_terms.iter() {
+			self.name_postings.entry(term.clone()).or_default().insert(id);
+		}
+
+		self.total_length += tokens.len();
+		self.ids.insert(path.to_path_buf(), id);
+		self.documents.insert(id, Document {
+			name,
+			path: path.to_path_buf(),
+			content: content.to_string(),
+			name_terms,
+			length: tokens.len(),
+		});
+	}
+
+	pub fn remove(&mut self, path: &Path) {
+		let Some(id) = self.ids.remove(path) else {
+			return
+		};
+
+		if let Some(document) = self.documents.remove(&id) {
+			self.total_length -= document.length;
+			for token in tokenize(&document.content) {
+				if let Some(docs) = self.postings.get_mut(&token.term) {
+					docs.remove(&id);
+					if docs.is_empty() {
+						self.postings.remove(&token.term);
+					}
+				}
+			}
+			for term in document.name_terms.iter() {
+				if let Some(docs) = self.name_postings.get_mut(term) {
+					docs.remove(&id);
+					if docs.is_empty() {
+						self.name_postings.remove(term);
+					}
+				}
+			}
+		}
+	}
+
+	// drops everything below `directory` and reads back whatever
+	// notes currently exist there, used after folder operations
+	pub fn reindex_directory(&mut self, directory: &Path) {
+		let stale: Vec<PathBuf> = self.ids
+			.keys()
+			.filter(|path| path.starts_with(directory))
+			.cloned()
+			.collect();
+		stale.iter().for_each(|path| self.remove(path));
+
+		if directory.is_dir() {
+			let directory_index = Self::build(directory);
+			for document in directory_index.documents.into_values() {
+				self.insert(&document.path, &document.content);
+			}
+		}
+	}
+
+	pub fn rename(&mut self, old_path: &Path, new_path: &Path) {
+		let content = match self.ids.get(old_path) {
+			Some(id) => self.documents[id].content.clone(),
+			None => return
+		};
+		self.remove(old_path);
+		self.insert(new_path, &content);
+	}
+
+	pub fn search(&self, query: &str, limit: usize) -> Vec<SearchResult> {
+		let Some(query) = Query::parse(query) else {
+			return vec![]
+		};
+
+		let matches = self.evaluate(&query);
+		let terms = self.scoring_terms(&query);
+
+		let mut results: Vec<SearchResult> = matches.iter().map(|id| {
+			let document = &self.documents[id];
+			SearchResult {
+				name: document.name.clone(),
+				path: document.path.clone(),
+				score: self.score(*id, &terms),
+				snippets: snippets(&document.content, &query),
+			}
+		}).collect();
+
+		results.sort_by(|a, b| b.score
+			.partial_cmp(&a.score)
+			.unwrap_or(std::cmp::Ordering::Equal)
+			.then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+		results.truncate(limit);
+		results
+	}
+
+	fn evaluate(&self, query: &Query) -> HashSet<DocId> {
+		match query {
+			Query::Term(term) => self.postings
+				.get(term)
+				.into_iter()
+				.flat_map(|docs| docs.keys())
+				.chain(self.name_postings.get(term).into_iter().flatten())
+				.copied()
+				.collect(),
+			Query::Prefix(prefix) => self.prefix_terms(prefix)
+				.flat_map(|(_, docs)| docs.keys())
+				.chain(self.name_postings
+					.range(prefix.to_string()..)
+					.take_while(|(term, _)| term.starts_with(prefix.as_str()))
+					.flat_map(|(_, docs)| docs))
+				.copied()
+				.collect(),
+			Query::Phrase(terms) => self.phrase_matches(terms),
+			Query::And(queries) => {
+				let (negated, positive): (Vec<&Query>, Vec<&Query>) = queries
+					.iter()
+					.partition(|q| matches!(q, Query::Not(_)));
+
+				let mut result: Option<HashSet<DocId>> = None;
+				for query in positive {
+					let docs = self.evaluate(query);
+					result = Some(match result {
+						Some(result) => result.intersection(&docs).copied().collect(),
+						None => docs
+					});
+				}
+
+				let mut result = result.unwrap_or_else(|| self.all_documents());
+				for query in negated {
+					if let Query::Not(query) = query {
+						for id in self.evaluate(query) {
+							result.remove(&id);
+						}
+					}
+				}
+				result
+			},
+			Query::Or(queries) => queries
+				.iter()
+				.flat_map(|q| self.evaluate(q))
+				.collect(),
+			Query::Not(query) => {
+				let excluded = self.evaluate(query);
+				self.all_documents()
+					.difference(&excluded)
+					.copied()
+					.collect()
+			},
+		}
+	}
+
+	fn all_documents(&self) -> HashSet<DocId> {
+		self.documents.keys().copied().collect()
+	}
+
+	fn prefix_terms<'a>(
+		&'a self,
+		prefix: &'a str
+	) -> impl Iterator<Item = (&'a String, &'a HashMap<DocId, Vec<usize>>)> {
+		self.postings
+			.range(prefix.to_string()..)
+			.take_while(move |(term, _)| term.starts_with(prefix))
+	}
+
+	fn phrase_matches(&self, terms: &[String]) -> HashSet<DocId> {
+		let Some(first) = terms.first().and_then(|t| self.postings.get(t)) else {
+			return HashSet::new()
+		};
+
+		first.iter().filter(|(id, positions)| {
+			positions.iter().any(|start| {
+				terms.iter().enumerate().skip(1).all(|(offset, term)| {
+					self.postings
+						.get(term)
+						.and_then(|docs| docs.get(id))
+						.map(|p| p.binary_search(&(start + offset)).is_ok())
+						.unwrap_or(false)
+				})
+			})
+		})
+		.map(|(id, _)| *id)
+		.collect()
+	}
+
+	// collects the index terms that contribute to the ranking,
+	// negated parts of the query never add to the score
+	fn scoring_terms(&self, query: &Query) -> Vec<String> {
+		match query {
+			Query::Term(term) => vec![term.clone()],
+			Query::Prefix(prefix) => self.prefix_terms(prefix)
+				.map(|(term, _)| term.clone())
+				.chain(self.name_postings
+					.range(prefix.to_string()..)
+					.take_while(|(term, _)| term.starts_with(prefix.as_str()))
+					.map(|(term, _)| term.clone()))
+				.collect::<HashSet<String>>()
+				.into_iter()
+				.collect(),
+			Query::Phrase(terms) => terms.clone(),
+			Query::And(queries) | Query::Or(queries) => queries
+				.iter()
+				.flat_map(|q| self.scoring_terms(q))
+				.collect(),
+			Query::Not(_) => vec![],
+		}
+	}
+
+	fn score(&self, id: DocId, terms: &[String]) -> f32 {
+		let document = &self.documents[&id];
+		let nbr_documents = self.documents.len() as f32;
+		let average_length = (self.total_length as f32 / nbr_documents).max(1.0);
+
+		terms.iter().map(|term| {
+			let mut score = 0.0;
+			if let Some((docs, positions)) = self.postings
+				.get(term)
+				.and_then(|docs| docs.get(&id).map(|positions| (docs, positions)))
+			{
+				let frequency = positions.len() as f32;
+				let nbr_matching = docs.len() as f32;
+				let idf = ((nbr_documents - nbr_matching + 0.5) / (nbr_matching + 0.5) + 1.0).ln();
+				let length_norm = 1.0 - B + B * document.length as f32 / average_length;
+				score += idf * frequency * (K1 + 1.0) / (frequency + K1 * length_norm);
+			}
+
+			if document.name_terms.contains(term) {
+				score += NAME_BOOST;
+			}
+			score
+		}).sum()
+	}
+}
+
+impl Query {
+	// Grammar: terms are and-ed by default, `OR` joins alternatives,
+	// `NOT term` or `-term` excludes, `"a b"` is a phrase, `pre*` is a prefix
+	// and parentheses group sub queries.
+	pub fn parse(input: &str) -> Option<Query> {
+		let lexemes = lex(input);
+		let mut position = 0;
+		let query = parse_or(&lexemes, &mut position, 0);
+		query.map(Query::simplify)
+	}
+
+	fn simplify(self) -> Query {
+		match self {
+			Query::And(mut queries) if queries.len() == 1 => queries.remove(0).simplify(),
+			Query::Or(mut queries) if queries.len() == 1 => queries.remove(0).simplify(),
+			Query::And(queries) => Query::And(queries.into_iter().map(Query::simplify).collect()),
+			Query::Or(queries) => Query::Or(queries.into_iter().map(Query::simplify).collect()),
+			Query::Not(query) => Query::Not(Box::new(query.simplify())),
+			query => query
+		}
+	}
+
+	// character ranges in `content` that match a positive part of the query
+	pub fn match_ranges(&self, content: &str) -> Vec<Range<usize>> {
+		let tokens = tokenize(content);
+		let mut ranges = vec![];
+		self.collect_ranges(&tokens, &mut ranges);
+		ranges.sort_by_key(|r| r.start);
+		ranges.dedup();
+		ranges
+	}
+
+	fn collect_ranges(&self, tokens: &[Token], ranges: &mut Vec<Range<usize>>) {
+		match self {
+			Query::Term(term) => ranges.extend(tokens
+				.iter()
+				.filter(|t| &t.term == term)
+				.map(|t| t.range.clone())),
+			Query::Prefix(prefix) => ranges.extend(tokens
+				.iter()
+				.filter(|t| t.term.starts_with(prefix.as_str()))
+				.map(|t| t.range.clone())),
+			Query::Phrase(terms) => {
+				if terms.is_empty() || tokens.len() < terms.len() {
+					return
+				}
+				for window in tokens.windows(terms.len()) {
+					if window.iter().zip(terms).all(|(t, term)| &t.term == term) {
+						ranges.push(window[0].range.start..window[terms.len() - 1].range.end);
+					}
+				}
+			},
+			Query::And(queries) | Query::Or(queries) => {
+				queries.iter().for_each(|q| q.collect_ranges(tokens, ranges));
+			},
+			Query::Not(_) => {},
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Lexeme {
+	Word(String),
+	Phrase(String),
+	And,
+	Or,
+	Not,
+	Open,
+	Close,
+}
+
+fn lex(input: &str) -> Vec<Lexeme> {
+	let mut lexemes = vec![];
+	let mut chars = input.chars().peekable();
+
+	while let Some(&c) = chars.peek() {
+		match c {
+			'"' => {
+				chars.next();
+				let phrase: String = chars.by_ref().take_while(|c| *c != '"').collect();
+				lexemes.push(Lexeme::Phrase(phrase));
+			},
+			'(' => { chars.next(); lexemes.push(Lexeme::Open); },
+			')' => { chars.next(); lexemes.push(Lexeme::Close); },
+			'-' => { chars.next(); lexemes.push(Lexeme::Not); },
+			c if c.is_whitespace() => { chars.next(); },
+			_ => {
+				let mut word = String::new();
+				while let Some(&c) = chars.peek() {
+					if c.is_whitespace() || c == '(' || c == ')' || c == '"' {
+						break;
+					}
+					word.push(c);
+					chars.next();
+				}
+				lexemes.push(match word.as_str() {
+					"AND" => Lexeme::And,
+					"OR" => Lexeme::Or,
+					"NOT" => Lexeme::Not,
+					_ => Lexeme::Word(word)
+				});
+			}
+		}
+	}
+
+	lexemes
+}
+
+// `depth` counts the open parentheses, a `)` without a matching `(` is skipped
+fn parse_or(lexemes: &[Lexeme], position: &mut usize, depth: usize) -> Option<Query> {
+	let mut alternatives = vec![];
+	if let Some(query) = parse_and(lexemes, position, depth) {
+		alternatives.push(query);
+	}
+
+	while lexemes.get(*position) == Some(&Lexeme::Or) {
+		*position += 1;
+		if let Some(query) = parse_and(lexemes, position, depth) {
+			alternatives.push(query);
+		}
+	}
+
+	match alternatives.len() {
+		0 => None,
+		_ => Some(Query::Or(alternatives))
+	}
+}
+
+fn parse_and(lexemes: &[Lexeme], position: &mut usize, depth: usize) -> Option<Query> {
+	let mut queries = vec![];
+
+	while let Some(lexeme) = lexemes.get(*position) {
+		match lexeme {
+			Lexeme::Or => break,
+			Lexeme::Close if depth > 0 => break,
+			Lexeme::And | Lexeme::Close => *position += 1,
+			_ => {
+				if let Some(query) = parse_unary(lexemes, position, depth) {
+					queries.push(query);
+				}
+			}
+		}
+	}
+
+	match queries.len() {
+		0 => None,
+		_ => Some(Query::And(queries))
+	}
+}
+
+fn parse_unary(lexemes: &[Lexeme], position: &mut usize, depth: usize) -> Option<Query> {
+	let lexeme = lexemes.get(*position)?.clone();
+	*position += 1;
+
+	match lexeme {
+		Lexeme::Not => parse_unary(lexemes, position, depth)
+			.map(|query| Query::Not(Box::new(query))),
+		Lexeme::Open => {
+			let query = parse_or(lexemes, position, depth + 1);
+			if lexemes.get(*position) == Some(&Lexeme::Close) {
+				*position += 1;
+			}
+			query
+		},
+		Lexeme::Phrase(phrase) => {
+			let terms: Vec<String> = tokenize(&phrase).into_iter().map(|t| t.term).collect();
+			match terms.len() {
+				0 => None,
+				1 => Some(Query::Term(terms[0].clone())),
+				_ => Some(Query::Phrase(terms))
+			}
+		},
+		Lexeme::Word(word) => {
+			let is_prefix = word.ends_with('*');
+			let mut terms: Vec<String> = tokenize(&word).into_iter().map(|t| t.term).collect();
+			match (terms.len(), is_prefix) {
+				(0, _) => None,
+				(1, true) => Some(Query::Prefix(terms.remove(0))),
+				(1, false) => Some(Query::Term(terms.remove(0))),
+				// words like `foo-bar` or `v1.2` tokenize into several terms
+				_ => Some(Query::Phrase(terms))
+			}
+		},
+		Lexeme::And | Lexeme::Or | Lexeme::Close => None,
+	}
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+	let mut tokens = vec![];
+	let mut term = String::new();
+	let mut start = 0;
+
+	for (offset, c) in text.chars().enumerate() {
+		if c.is_alphanumeric() {
+			if term.is_empty() {
+				start = offset;
+			}
+			term.extend(c.to_lowercase());
+		}
+		else if !term.is_empty() {
+			tokens.push(Token { term: std::mem::take(&mut term), range: start..offset });
+		}
+	}
+
+	if !term.is_empty() {
+		let end = text.chars().count();
+		tokens.push(Token { term, range: start..end });
+	}
+
+	tokens
+}
+
+fn snippets(content: &str, query: &Query) -> Vec<String> {
+	let chars: Vec<char> = content.chars().collect();
+	let mut snippets = vec![];
+	let mut covered_until = 0;
+
+	for range in query.match_ranges(content) {
+		if range.start < covered_until {
+			continue;
+		}
+
+		let start = range.start.saturating_sub(SNIPPET_CONTEXT);
+		let end = (range.end + SNIPPET_CONTEXT).min(chars.len());
+		let mut snippet: String = chars[start..end]
+			.iter()
+			.map(|c| if c.is_whitespace() { ' ' } else { *c })
+			.collect();
+		snippet = snippet.trim().to_string();
+
+		if start > 0 {
+			snippet.insert(0, '…');
+		}
+		if end < chars.len() {
+			snippet.push('…');
+		}
+
+		snippets.push(snippet);
+		covered_until = end;
+
+		if snippets.len() == MAX_SNIPPETS {
+			break;
+		}
+	}
+
+	snippets
+}
+
+impl Vault {
+	pub fn search(&self, query: &str, limit: usize) -> Vec<SearchResult> {
+		match self.search_index().lock() {
+			Ok(index) => index.search(query, limit),
+			Err(_) => vec![]
+		}
+	}
+
+	fn search_index(&self) -> &Mutex<SearchIndex> {
+		self.state().search.get_or_init(|| Mutex::new(SearchIndex::build(self.root())))
+	}
+}
+
+#[derive(Debug)]
+pub struct Search;
+
+impl Search {
+	pub fn query(query: &str, limit: usize) -> Vec<SearchResult> {
+		Vault::current().search(query, limit)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn term(term: &str) -> Query {
+		Query::Term(term.to_string())
+	}
+
+	fn index(notes: &[(&str, &str)]) -> SearchIndex {
+		let mut index = SearchIndex::new();
+		for (name, content) in notes {
+			index.insert(&PathBuf::from(format!("/notes/{name}.note")), content);
+		}
+		index
+	}
+
+	fn result_names(index: &SearchIndex, query: &str) -> Vec<String> {
+		index.search(query, 10).into_iter().map(|result| result.name).collect()
+	}
+
+	#[test]
+	fn parses_terms_as_and() {
+		assert_eq!(Query::parse("Foo bar"), Some(Query::And(vec![term("foo"), term("bar")])));
+		assert_eq!(Query::parse("foo AND bar"), Query::parse("foo bar"));
+		assert_eq!(Query::parse("foo"), Some(term("foo")));
+		assert_eq!(Query::parse("  "), None);
+	}
+
+	#[test]
+	fn parses_or_not_phrase_and_prefix() {
+		assert_eq!(Query::parse("foo OR bar"), Some(Query::Or(vec![term("foo"), term("bar")])));
+		assert_eq!(Query::parse("-foo"), Some(Query::Not(Box::new(term("foo")))));
+		assert_eq!(Query::parse("NOT foo"), Query::parse("-foo"));
+		assert_eq!(
+			Query::parse("\"foo bar\""),
+			Some(Query::Phrase(vec!["foo".to_string(), "bar".to_string()]))
+		);
+		assert_eq!(Query::parse("fo*"), Some(Query::Prefix("fo".to_string())));
+	}
+
+	#[test]
+	fn parses_groups() {
+		assert_eq!(
+			Query::parse("(foo OR bar) baz"),
+			Some(Query::And(vec![Query::Or(vec![term("foo"), term("bar")]), term("baz")]))
+		);
+		// a missing `)` closes the group at the end of the query
+		assert_eq!(Query::parse("(foo bar"), Query::parse("foo bar"));
+	}
+
+	#[test]
+	fn skips_unmatched_close() {
+		assert_eq!(Query::parse("foo ) bar"), Query::parse("foo bar"));
+		assert_eq!(Query::parse(") foo"), Some(term("foo")));
+		assert_eq!(
+			Query::parse("(foo) ) OR bar"),
+			Some(Query::Or(vec![term("foo"), term("bar")]))
+		);
+	}
+
+	#[test]
+	fn finds_matching_notes() {
+		let index = index(&[
+			("one", "apples and pears"),
+			("two", "apples and plums"),
+			("three", "pears only"),
+		]);
+
+		let mut names = result_names(&index, "apples -plums");
+		names.sort();
+		assert_eq!(names, vec!["one"]);
+
+		let mut names = result_names(&index, "plums OR pears");
+		names.sort();
+		assert_eq!(names, vec!["one", "three", "two"]);
+
+		assert_eq!(result_names(&index, "\"and pears\""), vec!["one"]);
+		assert_eq!(result_names(&index, "plu*"), vec!["two"]);
+	}
+
+	#[test]
+	fn ranks_frequent_terms_higher() {
+		let index = index(&[
+			("once", "rust is a language among many other languages here"),
+			("often", "rust rust rust is a language among many other ones"),
+		]);
+		assert_eq!(result_names(&index, "rust"), vec!["often", "once"]);
+	}
+
+	#[test]
+	fn ranks_rare_terms_higher() {
+		let index = index(&[
+			("common", "cat dog"),
+			("rare", "cat axolotl"),
+			("other", "cat bird"),
+		]);
+		let results = index.search("cat OR axolotl", 10);
+		assert_eq!(results[0].name, "rare");
+		assert!(results[0].score > results[1].score);
+	}
+
+	#[test]
+	fn boosts_note_names() {
+		let index = index(&[
+			("garden", "notes about plants"),
+			("plants", "notes about plants"),
+		]);
+		assert_eq!(result_names(&index, "plants"), vec!["plants", "garden"]);
+	}
+
+	#[test]
+	fn forgets_removed_and_renamed_notes() {
+		let mut index = index(&[("one", "apples"), ("two", "apples")]);
+		index.remove(Path::new("/notes/one.note"));
+		assert_eq!(result_names(&index, "apples"), vec!["two"]);
+
+		index.rename(Path::new("/notes/two.note"), Path::new("/notes/three.note"));
+		assert_eq!(result_names(&index, "apples"), vec!["three"]);
+	}
+}
@@ -0,0 +1,123 @@
+use std::cmp::Ordering;
+use std::path::Path;
+
+use crate::config::ConfigOptions;
+use crate::notes::Note;
+use crate::vault::Vault;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortBy {
+	#[default]
+	Name,
+	Modified,
+	Created,
+	Size,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortDirection {
+	#[default]
+	Ascending,
+	Descending,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SortMode {
+	pub by: SortBy,
+	pub direction: SortDirection,
+}
+
+impl SortBy {
+	pub fn as_str(&self) -> &str {
+		match self {
+			SortBy::Name => "name",
+			SortBy::Modified => "modified",
+			SortBy::Created => "created",
+			SortBy::Size => "size",
+		}
+	}
+}
+
+impl SortDirection {
+	pub fn as_str(&self) -> &str {
+		match self {
+			SortDirection::Ascending => "asc",
+			SortDirection::Descending => "desc",
+		}
+	}
+}
+
+impl SortMode {
+	pub fn new(by: SortBy, direction: SortDirection) -> Self {
+		Self { by, direction }
+	}
+
+	// stored as e.g. `modified-desc` in the metainfos file
+	pub fn to_config_value(&self) -> String {
+		format!("{}-{}", self.by.as_str(), self.direction.as_str())
+	}
+
+	pub fn from_config_value(value: &str) -> Option<Self> {
+		let (by, direction) = value.split_once('-')?;
+
+		let by = match by {
+			"name" => SortBy::Name,
+			"modified" => SortBy::Modified,
+			"created" => SortBy::Created,
+			"size" => SortBy::Size,
+			_ => return None
+		};
+
+		let direction = match direction {
+			"asc" => SortDirection::Ascending,
+			"desc" => SortDirection::Descending,
+			_ => return None
+		};
+
+		Some(Self { by, direction })
+	}
+
+	pub fn for_directory(path: &Path) -> Self {
+		Vault::current().sort_mode(path)
+	}
+
+	pub fn set_for_directory(&self, path: &Path) {
+		let _ = Vault::current().set_sort_mode(path, *self);
+	}
+
+	pub fn sort(&self, notes: &mut [Note]) {
+		notes.sort_by(|a, b| {
+			let ordering = match self.by {
+				SortBy::Name => Ordering::Equal,
+				SortBy::Modified => a.metadata.modified.cmp(&b.metadata.modified),
+				SortBy::Created => a.metadata.created.cmp(&b.metadata.created),
+				SortBy::Size => a.metadata.file_size.cmp(&b.metadata.file_size),
+			};
+			// notes with equal keys keep a stable alphabetical order
+			let ordering = ordering.then_with(||
+				a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+
+			match self.direction {
+				SortDirection::Ascending => ordering,
+				SortDirection::Descending => ordering.reverse(),
+			}
+		});
+	}
+}
+
+impl Vault {
+	pub fn sort_mode(&self, directory: &Path) -> SortMode {
+		self.config()
+			.meta_info(&directory.display().to_string(), ConfigOptions::SortOrder)
+			.and_then(|value| SortMode::from_config_value(&value))
+			.unwrap_or_default()
+	}
+
+	pub fn set_sort_mode(&self, directory: &Path, sort_mode: SortMode) -> anyhow::Result<()> {
+		self.config().set_meta_value(
+			&directory.display().to_string(),
+			ConfigOptions::SortOrder,
+			sort_mode.to_config_value()
+		)
+	}
+}
@@ -0,0 +1,151 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use regex::Regex;
+
+use crate::notes::note_paths;
+use crate::vault::Vault;
+
+static TAG: OnceLock<Regex> = OnceLock::new();
+static INLINE_CODE: OnceLock<Regex> = OnceLock::new();
+
+// which tags every note contains, notes are looked up by tag from here
+#[derive(Debug, Default)]
+pub struct TagIndex {
+	tags: HashMap<PathBuf, Vec<String>>,
+}
+
+impl TagIndex {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn build(root: &Path) -> Self {
+		let mut index = Self::new();
+		for path in note_paths(root) {
+			if let Ok(content) = std::fs::read_to_string(&path) {
+				index.insert(&path, &content);
+			}
+		}
+		index
+	}
+
+	pub fn insert(&mut self, path: &Path, content: &str) {
+		let tags = Tags::parse(content);
+		match tags.is_empty() {
+			true => self.tags.remove(path),
+			false => self.tags.insert(path.to_path_buf(), tags),
+		};
+	}
+
+	pub fn remove(&mut self, path: &Path) {
+		self.tags.remove(path);
+	}
+
+	pub fn rename(&mut self, old_path: &Path, new_path: &Path) {
+		if let Some(tags) = self.tags.remove(old_path) {
+			self.tags.insert(new_path.to_path_buf(), tags);
+		}
+	}
+
+	pub fn reindex_directory(&mut self, directory: &Path) {
+		self.tags.retain(|path, _| !path.starts_with(directory));
+		if directory.is_dir() {
+			self.tags.extend(Self::build(directory).tags);
+		}
+	}
+
+	// every tag with the number of notes using it, sorted by name
+	pub fn all(&self) -> Vec<(String, usize)> {
+		let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+		for tag in self.tags.values().flatten() {
+			*counts.entry(tag).or_default() += 1;
+		}
+		counts.into_iter().map(|(tag, count)| (tag.to_string(), count)).collect()
+	}
+
+	pub fn notes(&self, tag: &str) -> Vec<PathBuf> {
+		let tag = tag.trim_start_matches('#').to_lowercase();
+		let mut notes: Vec<PathBuf> = self.tags
+			.iter()
+			.filter(|(_, tags)| tags.contains(&tag))
+			.map(|(path, _)| path.clone())
+			.collect();
+		notes.sort();
+		notes
+	}
+}
+
+impl Vault {
+	pub fn tags(&self) -> Vec<(String, usize)> {
+		match self.tag_index().lock() {
+			Ok(index) => index.all(),
+			Err(_) => vec![]
+		}
+	}
+
+	pub fn tagged_notes(&self, tag: &str) -> Vec<PathBuf> {
+		match self.tag_index().lock() {
+			Ok(index) => index.notes(tag),
+			Err(_) => vec![]
+		}
+	}
+
+	fn tag_index(&self) -> &Mutex<TagIndex> {
+		self.state().tags.get_or_init(|| Mutex::new(TagIndex::build(self.root())))
+	}
+}
+
+#[derive(Debug)]
+pub struct Tags;
+
+impl Tags {
+	// `#tag` tokens of a note, lowercased and without duplicates. Tags
+	// inside code and numbers like `#1` are left alone
+	pub fn parse(content: &str) -> Vec<String> {
+		let mut tags: Vec<String> = vec![];
+		let mut in_code_block = false;
+
+		for line in content.lines() {
+			if line.trim_start().starts_with("```") {
+				in_code_block = !in_code_block;
+				continue
+			}
+			if in_code_block {
+				continue
+			}
+
+			let line = inline_code().replace_all(line, "");
+			for captures in tag().captures_iter(&line) {
+				let tag = captures[1].trim_end_matches(['/', '-']).to_lowercase();
+				if tag.chars().all(|c| c.is_numeric()) || tags.contains(&tag) {
+					continue
+				}
+				tags.push(tag);
+			}
+		}
+
+		tags
+	}
+
+	pub fn all() -> Vec<(String, usize)> {
+		Vault::current().tags()
+	}
+
+	pub fn notes(tag: &str) -> Vec<PathBuf> {
+		Vault::current().tagged_notes(tag)
+	}
+}
+
+// a `#` that does not follow a word, so anchors in urls and headings
+// (`# Title`) are not taken for tags
+fn tag() -> &'static Regex {
+	TAG.get_or_init(|| {
+		Regex::new(r"(?:^|[^\p{L}\p{N}_#&/])#([\p{L}\p{N}_][\p{L}\p{N}_/-]*)").unwrap()
+	})
+}
+
+fn inline_code() -> &'static Regex {
+	INLINE_CODE.get_or_init(|| Regex::new(r"`[^`]*`").unwrap())
+}
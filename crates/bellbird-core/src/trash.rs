@@ -0,0 +1,291 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use configparser::ini::Ini;
+
+use crate::journal::Operation;
+use crate::notes::note_paths;
+use crate::vault::Vault;
+
+// modelled after the XDG trash layout, trashed entries live in `files`
+// and each one has a matching `.trashinfo` file in `info`
+const TRASH_DIRECTORY: &str = ".trash";
+const FILES_DIRECTORY: &str = "files";
+const INFO_DIRECTORY: &str = "info";
+const INFO_EXTENSION: &str = "trashinfo";
+const INFO_SECTION: &str = "Trash Info";
+
+#[derive(Debug, Clone)]
+pub struct TrashItem {
+	pub name: String,
+	pub trashed_path: PathBuf,
+	pub original_path: PathBuf,
+	// unix timestamp in seconds
+	pub deletion_date: i64,
+	pub is_directory: bool,
+}
+
+impl Vault {
+	pub fn trash_directory(&self) -> PathBuf {
+		self.root().join(TRASH_DIRECTORY)
+	}
+
+	pub(crate) fn trash_files_directory(&self) -> Result<PathBuf> {
+		self.trash_sub_directory(FILES_DIRECTORY)
+	}
+
+	fn trash_sub_directory(&self, name: &str) -> Result<PathBuf> {
+		let path = self.trash_directory().join(name);
+		if !path.exists() {
+			fs::create_dir_all(&path)?;
+		}
+		Ok(path)
+	}
+
+	fn trash_info_path(&self, name: &str) -> Result<PathBuf> {
+		let mut path = self.trash_sub_directory(INFO_DIRECTORY)?;
+		path.push(format!("{name}.{INFO_EXTENSION}"));
+		Ok(path)
+	}
+
+	pub fn move_to_trash(&self, path: &Path) -> Result<PathBuf> {
+		if !path.exists() {
+			return Err(anyhow::anyhow!(
+				"Could not move to trash, path does not exist: {}",
+				path.display()
+			));
+		}
+
+		let file_name = path.file_name()
+			.map(|name| name.to_string_lossy().to_string())
+			.unwrap_or_default();
+
+		let trashed_path = unique_path(&self.trash_files_directory()?, &file_name);
+		let trashed_name = trashed_path.file_name().unwrap().to_string_lossy().to_string();
+		let is_directory = path.is_dir();
+
+		let mut info = Ini::new_cs();
+		info.set(INFO_SECTION, "Path", Some(path.display().to_string()));
+		info.set(INFO_SECTION, "DeletionDate", Some(now().to_string()));
+		info.write(self.trash_info_path(&trashed_name)?)?;
+
+		fs::rename(path, &trashed_path).map_err(|e| {
+			eprintln!("Failed to move to trash: {:?}", e);
+			let _ = fs::remove_file(self.trash_info_path(&trashed_name).unwrap_or_default());
+			e
+		})?;
+
+		// pins and the like come back along with the item on a restore
+		let _ = self.config().move_meta_infos(
+			&path.display().to_string(),
+			&trashed_path.display().to_string()
+		);
+
+		match is_directory {
+			true => {
+				self.reindex_directory(path);
+				self.reindex_note_ids(path);
+				self.reindex_note_ids(&trashed_path);
+			},
+			false => {
+				self.move_note_id(path, &trashed_path);
+				self.unindex_note(path);
+			},
+		}
+
+		Ok(trashed_path)
+	}
+
+	// most recently deleted first
+	pub fn trash_items(&self) -> Vec<TrashItem> {
+		let mut items = vec![];
+		let Ok(files_dir) = self.trash_files_directory() else {
+			return items
+		};
+		let Ok(entries) = fs::read_dir(files_dir) else {
+			return items
+		};
+
+		for entry in entries.flatten() {
+			let trashed_path = entry.path();
+			let name = entry.file_name().to_string_lossy().to_string();
+			// the ids of trashed notes, see `NoteIds`
+			if name.starts_with('.') {
+				continue
+			}
+
+			let mut info = Ini::new_cs();
+			let (original_path, deletion_date) = match self.trash_info_path(&name)
+				.ok()
+				.and_then(|info_path| info.load(info_path).ok())
+			{
+				Some(_) => (
+					info.get(INFO_SECTION, "Path").map(PathBuf::from).unwrap_or_default(),
+					info.get(INFO_SECTION, "DeletionDate")
+						.and_then(|date| date.parse().ok())
+						.unwrap_or_default()
+				),
+				None => (PathBuf::new(), 0)
+			};
+
+			items.push(TrashItem {
+				is_directory: trashed_path.is_dir(),
+				name,
+				trashed_path,
+				original_path,
+				deletion_date,
+			});
+		}
+
+		items.sort_by_key(|item| Reverse(item.deletion_date));
+		items
+	}
+
+	pub fn restore_from_trash(&self, item: &TrashItem) -> Result<PathBuf> {
+		let original_path = match item.original_path.as_os_str().is_empty() {
+			true => self.root().join(&item.name),
+			false => item.original_path.clone()
+		};
+
+		let restored_path = self.restore_trashed(&item.trashed_path, &original_path)?;
+		self.record(Operation::Restore {
+			trashed_path: item.trashed_path.clone(),
+			path: restored_path.clone(),
+		});
+		Ok(restored_path)
+	}
+
+	// puts a trashed item back at `original_path`, if that path has been taken
+	// in the meantime the restored item gets a unique name next to it
+	pub fn restore_trashed(&self, trashed_path: &Path, original_path: &Path) -> Result<PathBuf> {
+		let parent = original_path.parent().unwrap_or(Path::new("/"));
+		if !parent.exists() {
+			fs::create_dir_all(parent)?;
+		}
+
+		let trashed_name = trashed_path.file_name()
+			.map(|name| name.to_string_lossy().to_string())
+			.unwrap_or_default();
+		let file_name = original_path.file_name()
+			.map(|name| name.to_string_lossy().to_string())
+			.unwrap_or(trashed_name.clone());
+		let restored_path = unique_path(parent, &file_name);
+
+		fs::rename(trashed_path, &restored_path).map_err(|e| {
+			eprintln!("Failed to restore from trash: {:?}", e);
+			e
+		})?;
+		let _ = fs::remove_file(self.trash_info_path(&trashed_name)?);
+		let _ = self.config().move_meta_infos(
+			&trashed_path.display().to_string(),
+			&restored_path.display().to_string()
+		);
+
+		if restored_path.is_dir() {
+			self.reindex_note_ids(trashed_path);
+			self.reindex_note_ids(&restored_path);
+			self.reindex_directory(&restored_path);
+		}
+		else if let Ok(content) = fs::read_to_string(&restored_path) {
+			self.move_note_id(trashed_path, &restored_path);
+			self.index_note(&restored_path, &content);
+		}
+
+		Ok(restored_path)
+	}
+
+	pub fn delete_permanently(&self, item: &TrashItem) -> Result<()> {
+		// the revisions are kept by id, they would outlive the notes otherwise
+		let note_ids: Vec<String> = match item.is_directory {
+			true => note_paths(&item.trashed_path).filter_map(|path| self.note_id(&path)).collect(),
+			false => self.note_id(&item.trashed_path).into_iter().collect(),
+		};
+
+		match item.is_directory {
+			true => fs::remove_dir_all(&item.trashed_path)?,
+			false => fs::remove_file(&item.trashed_path)?,
+		}
+		let _ = fs::remove_file(self.trash_info_path(&item.name)?);
+		let mut config = self.config();
+		let _ = config.remove_meta_infos(&item.trashed_path.display().to_string());
+		match item.is_directory {
+			true => self.reindex_note_ids(&item.trashed_path),
+			false => if let Some(id) = self.remove_note_id(&item.trashed_path) {
+				let _ = config.remove_meta_infos(&id);
+			},
+		}
+		for id in note_ids {
+			let _ = fs::remove_dir_all(self.history_directory().join(id));
+		}
+		Ok(())
+	}
+
+	pub fn empty_trash(&self) -> Result<()> {
+		for item in self.trash_items() {
+			self.delete_permanently(&item)?;
+		}
+		Ok(())
+	}
+}
+
+#[derive(Debug)]
+pub struct Trash;
+
+impl Trash {
+	pub fn trash_directory() -> Option<PathBuf> {
+		Some(Vault::current().trash_directory())
+	}
+
+	pub fn move_to_trash(path: &Path) -> Result<PathBuf> {
+		Vault::current().move_to_trash(path)
+	}
+
+	pub fn list() -> Vec<TrashItem> {
+		Vault::current().trash_items()
+	}
+
+	pub fn restore(item: &TrashItem) -> Result<PathBuf> {
+		Vault::current().restore_from_trash(item)
+	}
+
+	pub fn restore_to(trashed_path: &Path, original_path: &Path) -> Result<PathBuf> {
+		Vault::current().restore_trashed(trashed_path, original_path)
+	}
+
+	pub fn delete_permanently(item: &TrashItem) -> Result<()> {
+		Vault::current().delete_permanently(item)
+	}
+
+	pub fn empty() -> Result<()> {
+		Vault::current().empty_trash()
+	}
+}
+
+// appends a counter to the file stem until the name is free
+fn unique_path(directory: &Path, file_name: &str) -> PathBuf {
+	let mut path = directory.join(file_name);
+	let file_path = Path::new(file_name);
+	let stem = file_path.file_stem()
+		.map(|stem| stem.to_string_lossy().to_string())
+		.unwrap_or_default();
+	let extension = file_path.extension()
+		.map(|ext| format!(".{}", ext.to_string_lossy()))
+		.unwrap_or_default();
+
+	let mut counter = 2;
+	while path.exists() {
+		path = directory.join(format!("{stem} {counter}{extension}"));
+		counter += 1;
+	}
+	path
+}
+
+fn now() -> i64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|duration| duration.as_secs() as i64)
+		.unwrap_or_default()
+}
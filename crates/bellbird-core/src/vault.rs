@@ -0,0 +1,265 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use anyhow::Result;
+
+use crate::config::Config;
+use crate::directories::Directories;
+use crate::ids::IdIndex;
+use crate::journal::JournalStacks;
+use crate::links::LinkGraph;
+use crate::notes::is_note;
+use crate::search::SearchIndex;
+use crate::tags::TagIndex;
+
+// where `Vault::open` keeps the metainfos, hidden files are skipped
+// everywhere so it never shows up as a note
+const META_INFO_FILE: &str = ".bellbird-metainfos";
+
+// handed out by `Vault::current` as long as the notes directory stays the
+// same, so its indexes and its journal live on between calls
+static CURRENT: OnceLock<Mutex<Option<Vault>>> = OnceLock::new();
+
+// a notes directory together with the metainfos of its notes and folders.
+// The note, folder, trash and history operations are implemented on it
+// next to their static counterparts, e.g. `Vault::create_note` in
+// notes.rs. `Notes`, `Directories`, `Trash` and `History` work on the
+// vault the app is set up with, see `Vault::current`. Every vault has
+// indexes and an undo journal of its own, clones share them
+#[derive(Debug, Clone)]
+pub struct Vault {
+	root: PathBuf,
+	meta_info_file: PathBuf,
+	state: Arc<VaultState>,
+}
+
+// the indexes are built on first use, updates before that are skipped
+// since the build reads the current state from disk anyway
+#[derive(Debug, Default)]
+pub(crate) struct VaultState {
+	pub(crate) search: OnceLock<Mutex<SearchIndex>>,
+	pub(crate) links: OnceLock<Mutex<LinkGraph>>,
+	pub(crate) tags: OnceLock<Mutex<TagIndex>>,
+	pub(crate) ids: OnceLock<Mutex<IdIndex>>,
+	pub(crate) journal: Mutex<JournalStacks>,
+}
+
+impl PartialEq for Vault {
+	fn eq(&self, other: &Self) -> bool {
+		self.root == other.root && self.meta_info_file == other.meta_info_file
+	}
+}
+
+impl Vault {
+	pub fn new(root: &Path, meta_info_file: &Path) -> Self {
+		Self {
+			root: root.to_path_buf(),
+			meta_info_file: meta_info_file.to_path_buf(),
+			state: Arc::new(VaultState::default()),
+		}
+	}
+
+	// a self-contained vault that keeps its metainfos inside `root`,
+	// the directory is created if it doesn't exist yet
+	pub fn open(root: &Path) -> Result<Self> {
+		fs::create_dir_all(root)?;
+		Ok(Self::new(root, &root.join(META_INFO_FILE)))
+	}
+
+	// the notes directory from the settings with the app's metainfos file,
+	// a fresh vault once the notes directory changed
+	pub fn current() -> Self {
+		let root = Directories::bb_root_directory().unwrap_or_default();
+		let meta_info_file = Config::new().config_file(true).unwrap_or_default();
+		let vault = Self::new(&root, &meta_info_file);
+
+		let Ok(mut current) = CURRENT.get_or_init(|| Mutex::new(None)).lock() else {
+			return vault
+		};
+		match current.as_ref() {
+			Some(current) if *current == vault => current.clone(),
+			_ => {
+				*current = Some(vault.clone());
+				vault
+			}
+		}
+	}
+
+	pub fn root(&self) -> &Path {
+		&self.root
+	}
+
+	pub fn meta_info_file(&self) -> &Path {
+		&self.meta_info_file
+	}
+
+	// the metainfos store, pins, expanded folders, sort orders and so on
+	pub fn config(&self) -> Config {
+		Config::with_meta_info_file(&self.meta_info_file)
+	}
+
+	pub(crate) fn state(&self) -> &VaultState {
+		&self.state
+	}
+
+	// the hooks below keep the search, link and tag indexes up to date
+	pub(crate) fn index_note(&self, path: &Path, content: &str) {
+		if !is_note(path) {
+			return
+		}
+		with_built(&self.state.search, |index| index.insert(path, content));
+		with_built(&self.state.links, |graph| graph.insert(path, content));
+		with_built(&self.state.tags, |index| index.insert(path, content));
+	}
+
+	pub(crate) fn index_renamed_note(&self, old_path: &Path, new_path: &Path) {
+		with_built(&self.state.search, |index| index.rename(old_path, new_path));
+		with_built(&self.state.links, |graph| graph.rename(old_path, new_path));
+		with_built(&self.state.tags, |index| index.rename(old_path, new_path));
+	}
+
+	pub(crate) fn unindex_note(&self, path: &Path) {
+		with_built(&self.state.search, |index| index.remove(path));
+		with_built(&self.state.links, |graph| graph.remove(path));
+		with_built(&self.state.tags, |index| index.remove(path));
+	}
+
+	pub(crate) fn reindex_directory(&self, path: &Path) {
+		with_built(&self.state.search, |index| index.reindex_directory(path));
+		with_built(&self.state.links, |graph| graph.reindex_directory(path));
+		with_built(&self.state.tags, |index| index.reindex_directory(path));
+	}
+}
+
+// runs `f` on the index unless it hasn't been built yet
+pub(crate) fn with_built<T, F>(index: &OnceLock<Mutex<T>>, f: F)
+where
+	F: FnOnce(&mut T)
+{
+	if let Some(index) = index.get() {
+		if let Ok(mut index) = index.lock() {
+			f(&mut index);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn temp_vault(name: &str) -> Vault {
+		let root = std::env::temp_dir()
+			.join(format!("bellbird-vault-{}-{name}", std::process::id()));
+		let _ = fs::remove_dir_all(&root);
+		Vault::open(&root).unwrap()
+	}
+
+	#[test]
+	fn vaults_keep_to_themselves() {
+		let first = temp_vault("first");
+		let second = temp_vault("second");
+		// built up front so the updates below have to go to the right index
+		for vault in [&first, &second] {
+			assert!(vault.search("compost", 10).is_empty());
+			assert!(vault.tags().is_empty());
+		}
+
+		let note = first.create_note(&first.root().join("Garden")).unwrap();
+		first.write_note(&note, "#plants need [[Compost]]").unwrap();
+		first.set_is_pinned(&note, true).unwrap();
+		let id = first.note_id(&note).unwrap();
+
+		assert_eq!(first.search("compost", 10).len(), 1);
+		assert!(second.search("compost", 10).is_empty());
+		assert_eq!(first.tags(), vec![("plants".to_string(), 1)]);
+		assert!(second.tags().is_empty());
+		assert_eq!(first.note_path(&id), Some(note.clone()));
+		assert_eq!(second.note_path(&id), None);
+		assert_eq!(first.resolve_link("garden", None), Some(note.clone()));
+		assert_eq!(second.resolve_link("garden", None), None);
+		assert!(first.root().join(".bellbird-ids").is_file());
+		assert!(!second.root().join(".bellbird-ids").exists());
+		assert!(first.is_pinned(&note));
+		assert!(!second.meta_info_file().exists());
+
+		assert!(first.can_undo());
+		assert!(!second.can_undo());
+		async_std::task::block_on(first.undo()).unwrap();
+		assert!(!first.is_pinned(&note));
+		assert!(first.can_redo());
+		assert!(!second.can_redo());
+
+		for vault in [first, second] {
+			let _ = fs::remove_dir_all(vault.root());
+		}
+	}
+
+	#[test]
+	fn empty_roots_keep_their_metainfos() {
+		// the metainfos live outside the notes directory, as with the app's
+		let root = temp_vault("empty").root().to_path_buf();
+		let vault = Vault::new(&root, &root.with_extension("metainfos"));
+		let note = vault.create_note(&vault.root().join("Garden")).unwrap();
+		vault.set_is_pinned(&note, true).unwrap();
+		let id = vault.note_id(&note).unwrap();
+
+		// as if the notes directory was unmounted and created again
+		fs::remove_dir_all(vault.root()).unwrap();
+		fs::create_dir_all(vault.root()).unwrap();
+		vault.refresh_note_ids();
+		let removed = vault.config()
+			.collect_stale_meta_infos(&vault, &Default::default())
+			.unwrap();
+		assert_eq!(removed, 0);
+		assert!(fs::read_to_string(vault.meta_info_file()).unwrap().contains(&id));
+
+		let _ = fs::remove_dir_all(vault.root());
+		let _ = fs::remove_file(vault.meta_info_file());
+	}
+
+	#[test]
+	fn deleted_notes_take_their_history_along() {
+		let vault = temp_vault("history");
+		let note = vault.create_note(&vault.root().join("Garden")).unwrap();
+		vault.snapshot(&note, "beans").unwrap();
+		let revisions_dir = vault.history_directory().join(vault.note_id(&note).unwrap());
+		assert!(revisions_dir.is_dir());
+
+		vault.delete_note(&note).unwrap();
+		for item in vault.trash_items() {
+			vault.delete_permanently(&item).unwrap();
+		}
+		assert!(!revisions_dir.exists());
+
+		let _ = fs::remove_dir_all(vault.root());
+	}
+
+	#[test]
+	fn renames_keep_existing_notes() {
+		let vault = temp_vault("rename");
+		let first = vault.create_note(&vault.root().join("A")).unwrap();
+		let second = vault.create_note(&vault.root().join("B")).unwrap();
+		vault.write_note(&first, "first").unwrap();
+
+		assert!(async_std::task::block_on(vault.rename_note(&second, &first)).is_err());
+		assert_eq!(fs::read_to_string(&first).unwrap(), "first");
+		assert!(second.exists());
+
+		// undoing the rename of `A` fails while its old name is taken again
+		let renamed = vault.root().join("C.note");
+		async_std::task::block_on(vault.rename_note(&first, &renamed)).unwrap();
+		let reused = vault.create_note(&first).unwrap();
+		vault.write_note(&reused, "reused").unwrap();
+		vault.record(crate::journal::Operation::Rename {
+			old_path: first.clone(),
+			new_path: renamed.clone(),
+			is_directory: false,
+		});
+		assert!(async_std::task::block_on(vault.undo()).is_err());
+		assert_eq!(fs::read_to_string(&first).unwrap(), "reused");
+		assert_eq!(fs::read_to_string(&renamed).unwrap(), "first");
+
+		let _ = fs::remove_dir_all(vault.root());
+	}
+}
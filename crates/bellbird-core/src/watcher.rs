@@ -0,0 +1,285 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use notify::event::{AccessKind, AccessMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+use walkdir::WalkDir;
+
+use crate::directories::Directories;
+use crate::ids::NoteIds;
+
+// changes arriving within this delay of each other are reported together,
+// a single save of another editor easily causes several events
+const BATCH_DELAY: Duration = Duration::from_millis(200);
+const MAX_BATCH_DURATION: Duration = Duration::from_secs(1);
+// writes of the app that never showed up as an event are forgotten after this
+const ACKNOWLEDGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// last known state of everything below the watched directory
+static WATCHED: OnceLock<Mutex<HashMap<PathBuf, FileState>>> = OnceLock::new();
+// state of files right after the app itself wrote them
+static ACKNOWLEDGED: OnceLock<Mutex<HashMap<PathBuf, (FileState, Instant)>>> = OnceLock::new();
+static ROOT: OnceLock<Mutex<PathBuf>> = OnceLock::new();
+static NOTIFY_WATCHER: OnceLock<Mutex<Option<RecommendedWatcher>>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FileState {
+	modified: Option<SystemTime>,
+	size: u64,
+	is_directory: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WatchEventKind {
+	Created,
+	Modified,
+	Removed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchEvent {
+	pub kind: WatchEventKind,
+	pub path: PathBuf,
+	pub is_directory: bool,
+}
+
+// listens to the platform's file notifications (inotify, FSEvents & co)
+// and compares the touched paths with their last known state, so an event
+// is only reported when something actually changed
+#[derive(Debug)]
+pub struct Watcher;
+
+impl Watcher {
+	// starts watching `root`, every batch of changes is sent as one message,
+	// the background thread stops once the receiver is dropped
+	pub fn start(root: &Path) -> Receiver<Vec<WatchEvent>> {
+		let (sender, receiver) = mpsc::channel();
+		let (notify_sender, notify_receiver) = mpsc::channel();
+
+		match notify::recommended_watcher(notify_sender) {
+			Ok(notify_watcher) => if let Ok(mut watcher) = Self::notify_watcher().lock() {
+				*watcher = Some(notify_watcher);
+			},
+			Err(e) => eprintln!("Could not watch the notes directory: {:?}", e),
+		}
+		Self::set_root(root);
+
+		thread::spawn(move || {
+			while let Ok(event) = notify_receiver.recv() {
+				let mut paths = HashSet::new();
+				let mut needs_rescan = collect_paths(event, &mut paths);
+
+				let batch_start = Instant::now();
+				while batch_start.elapsed() < MAX_BATCH_DURATION {
+					match notify_receiver.recv_timeout(BATCH_DELAY) {
+						Ok(event) => needs_rescan |= collect_paths(event, &mut paths),
+						Err(RecvTimeoutError::Timeout) => break,
+						Err(RecvTimeoutError::Disconnected) => return,
+					}
+				}
+
+				let events = Self::changes(paths, needs_rescan);
+				// notes moved or renamed by other programs keep their ids
+				if events.iter().any(|event| event.kind != WatchEventKind::Modified) {
+					NoteIds::refresh();
+				}
+
+				if !events.is_empty() && sender.send(events).is_err() {
+					break
+				}
+			}
+		});
+
+		receiver
+	}
+
+	// watches another directory from now on, without reporting
+	// everything in there as new
+	pub fn set_root(root: &Path) {
+		if let Ok(mut notify_watcher) = Self::notify_watcher().lock() {
+			if let Some(notify_watcher) = notify_watcher.as_mut() {
+				if let Ok(previous_root) = Self::root().lock() {
+					let _ = notify_watcher.unwatch(&previous_root);
+				}
+				if let Err(e) = notify_watcher.watch(root, RecursiveMode::Recursive) {
+					eprintln!("Could not watch ´{}´: {:?}", root.display(), e);
+				}
+			}
+		}
+
+		if let Ok(mut watched) = Self::watched().lock() {
+			*watched = scan(root);
+		}
+		if let Ok(mut watched_root) = Self::root().lock() {
+			*watched_root = root.to_path_buf();
+		}
+	}
+
+	// takes note of a change the app made itself so it isn't reported back
+	pub fn acknowledge(path: &Path) {
+		// nothing would ever pick it up again, e.g. in the `bellbird` command
+		if !Self::is_running() {
+			return
+		}
+		// writes outside the watched directory, e.g. into another `Vault`, never show up
+		if !Self::root().lock().is_ok_and(|root| is_watched(&root, path)) {
+			return
+		}
+		let Some(state) = file_state(path) else {
+			return
+		};
+		if let Ok(mut acknowledged) = Self::acknowledged().lock() {
+			acknowledged.insert(path.to_path_buf(), (state, Instant::now()));
+		}
+	}
+
+	fn is_running() -> bool {
+		NOTIFY_WATCHER
+			.get()
+			.and_then(|watcher| watcher.lock().ok())
+			.is_some_and(|watcher| watcher.is_some())
+	}
+
+	// compares the touched paths, or everything if events were lost,
+	// with what was known about them
+	fn changes(paths: HashSet<PathBuf>, needs_rescan: bool) -> Vec<WatchEvent> {
+		let Ok(root) = Self::root().lock().map(|root| root.clone()) else {
+			return vec![]
+		};
+		let (Ok(mut watched), Ok(mut acknowledged)) = (Self::watched().lock(), Self::acknowledged().lock()) else {
+			return vec![]
+		};
+		acknowledged.retain(|_, (_, time)| time.elapsed() < ACKNOWLEDGE_TIMEOUT);
+
+		let mut paths: Vec<PathBuf> = match needs_rescan {
+			true => watched.keys().cloned().chain(scan(&root).into_keys()).collect(),
+			false => paths.into_iter().filter(|path| is_watched(&root, path)).collect(),
+		};
+		paths.sort();
+		paths.dedup();
+
+		let mut events = vec![];
+		let mut position = 0;
+		while position < paths.len() {
+			let path = paths[position].clone();
+			position += 1;
+
+			let previous = watched.get(&path).copied();
+			let current = file_state(&path);
+			// acknowledged writes are consumed by the first look at the path
+			let acknowledged_state = acknowledged.remove(&path).map(|(state, _)| state);
+
+			let (kind, is_directory) = match (previous, current) {
+				(None, Some(state)) => {
+					// a folder moved in brings its content along
+					if state.is_directory {
+						paths.extend(scan(&path).into_keys());
+					}
+					(WatchEventKind::Created, state.is_directory)
+				},
+				(Some(state), None) => {
+					// so does a folder moved out
+					if state.is_directory {
+						paths.extend(watched.keys().filter(|p| p.starts_with(&path) && **p != path).cloned());
+					}
+					(WatchEventKind::Removed, state.is_directory)
+				},
+				// a folder's own timestamp changes with its content,
+				// that is reported through the entries inside it
+				(Some(_), Some(state)) if state.is_directory => {
+					watched.insert(path, state);
+					continue
+				},
+				(Some(previous_state), Some(state)) if previous_state != state => {
+					if acknowledged_state == Some(state) {
+						watched.insert(path, state);
+						continue
+					}
+					(WatchEventKind::Modified, false)
+				},
+				_ => continue
+			};
+
+			match current {
+				Some(state) => watched.insert(path.clone(), state),
+				None => watched.remove(&path),
+			};
+			events.push(WatchEvent { kind, path, is_directory });
+		}
+
+		events
+	}
+
+	fn root() -> &'static Mutex<PathBuf> {
+		ROOT.get_or_init(|| Mutex::new(PathBuf::new()))
+	}
+
+	fn watched() -> &'static Mutex<HashMap<PathBuf, FileState>> {
+		WATCHED.get_or_init(|| Mutex::new(HashMap::new()))
+	}
+
+	fn acknowledged() -> &'static Mutex<HashMap<PathBuf, (FileState, Instant)>> {
+		ACKNOWLEDGED.get_or_init(|| Mutex::new(HashMap::new()))
+	}
+
+	fn notify_watcher() -> &'static Mutex<Option<RecommendedWatcher>> {
+		NOTIFY_WATCHER.get_or_init(|| Mutex::new(None))
+	}
+}
+
+// adds the paths of the event, returns whether events were lost and
+// the whole directory has to be compared again
+fn collect_paths(event: notify::Result<Event>, paths: &mut HashSet<PathBuf>) -> bool {
+	let Ok(event) = event else {
+		return true
+	};
+	if event.need_rescan() {
+		return true
+	}
+	// reading a note doesn't change it
+	let is_read = matches!(
+		event.kind,
+		EventKind::Access(kind) if kind != AccessKind::Close(AccessMode::Write)
+	);
+	if !is_read {
+		paths.extend(event.paths);
+	}
+	false
+}
+
+// everything inside the root except hidden files and folders, like
+// the history or the trash
+fn is_watched(root: &Path, path: &Path) -> bool {
+	match path.strip_prefix(root) {
+		Ok(relative_path) => relative_path.components().next().is_some() && !relative_path
+			.components()
+			.any(|component| component.as_os_str().to_string_lossy().starts_with('.')),
+		Err(_) => false
+	}
+}
+
+fn scan(root: &Path) -> HashMap<PathBuf, FileState> {
+	WalkDir::new(root)
+		.min_depth(1)
+		.into_iter()
+		.filter_entry(|e| !Directories::is_hidden(e))
+		.flatten()
+		.filter_map(|entry| {
+			let state = file_state(entry.path())?;
+			Some((entry.into_path(), state))
+		})
+		.collect()
+}
+
+fn file_state(path: &Path) -> Option<FileState> {
+	let metadata = path.metadata().ok()?;
+	Some(FileState {
+		modified: metadata.modified().ok(),
+		size: metadata.len(),
+		is_directory: metadata.is_dir(),
+	})
+}
@@ -1,12 +1,23 @@
 use std::cell::RefCell;
+use std::path::PathBuf;
 use std::rc::Rc;
 use glib::MainContext;
 use gtk::prelude::*;
 
 use gtk::gio;
 
-use crate::editor_view::Editor;
+use bellbird_core::config::Settings;
+use bellbird_core::directories::Directories;
+use bellbird_core::journal::{Journal, Operation};
+use bellbird_core::notebooks::Notebooks;
+use bellbird_core::notes::Notes;
+use bellbird_core::sort::SortMode;
+
+use crate::editor_view::{self, Editor};
+use crate::preferences_window::PreferencesWindow;
+use crate::trash_view::TrashView;
 use crate::{
+	dialogue::Dialogue,
 	directory_tree::DirectoryTree,
 	notes_list::NotesList
 };
@@ -61,10 +72,448 @@ impl<'a> ActionEntries<'a> {
 		self.app.add_action_entries([action_refresh_notes]);
 	}
 
+	pub fn register_refresh_directories_action(&self) {
+		let directory_tree_clone = self.directory_tree.clone();
+		let action_refresh_directories = gio::ActionEntry::builder("refresh-directories")
+			.activate(move |_, _action, _parameter| {
+				directory_tree_clone.borrow_mut().refresh();
+			})
+			.build();
+
+		self.app.add_action_entries([action_refresh_directories]);
+	}
+
+	pub fn register_filter_tag_action(&self) {
+		let notes_list_clone = self.notes_list.clone();
+		let action_filter_tag = gio::ActionEntry::builder("filter-tag")
+			.parameter_type(Some(&String::static_variant_type()))
+			.activate(move |_, _action, parameter| {
+				let tag = parameter
+					.expect("Could not get Parameter")
+					.get::<String>()
+					.expect("The variant nees to be of type `String`");
+
+				MainContext::default().spawn_local(glib::clone!(
+					#[strong] notes_list_clone,
+					async move {
+						notes_list_clone.borrow_mut().show_tag(tag).await;
+					}
+				));
+			})
+			.build();
+
+		self.app.add_action_entries([action_filter_tag]);
+	}
+
+	pub fn register_refresh_tags_action(&self) {
+		let directory_tree_clone = self.directory_tree.clone();
+		let action_refresh_tags = gio::ActionEntry::builder("refresh-tags")
+			.activate(move |_, _action, _parameter| {
+				// the tree might be in the middle of refreshing itself,
+				// which includes the tags
+				if let Ok(directory_tree) = directory_tree_clone.try_borrow() {
+					directory_tree.refresh_tags();
+				}
+			})
+			.build();
+
+		self.app.add_action_entries([action_refresh_tags]);
+	}
+
+	pub fn register_move_to_action(&self) {
+		let editor_clone = self.editor.clone();
+		let notes_list_clone = self.notes_list.clone();
+		let directory_tree_clone = self.directory_tree.clone();
+		let action_move_to = gio::ActionEntry::builder("move-to")
+			.parameter_type(Some(&<(String, String)>::static_variant_type()))
+			.activate(move |_, _action, parameter| {
+				let (source, target) = parameter
+					.expect("Could not get Parameter")
+					.get::<(String, String)>()
+					.expect("The variant nees to be of type `(String, String)`");
+				let (source, target) = (PathBuf::from(source), PathBuf::from(target));
+
+				MainContext::default().spawn_local(glib::clone!(
+					#[strong] editor_clone,
+					#[strong] notes_list_clone,
+					#[strong] directory_tree_clone,
+					async move {
+						// pending changes have to be on disk before the file moves
+						if let Err(e) = editor_clone.borrow().flush_all() {
+							eprintln!("Could not save notes before moving: {:?}", e);
+							return
+						}
+
+						let result = match source.is_dir() {
+							true => Directories::move_to(&source, &target),
+							false => Notes::move_to(&source, &target).await,
+						};
+						let new_path = match result {
+							Ok(new_path) => new_path,
+							Err(e) => {
+								eprintln!("Could not move ´{}´: {:?}", source.display(), e);
+								return
+							}
+						};
+
+						editor_clone.borrow_mut().paths_moved(&source, &new_path).await;
+						directory_tree_clone.borrow_mut().refresh();
+
+						// the listed folder might have moved as well
+						let notes_path = notes_list_clone.borrow().path.clone();
+						match editor_view::moved_path(&notes_path, &source, &new_path) {
+							Some(path) => {
+								Directories::set_current_directory_path(&path);
+								directory_tree_clone.borrow().update_current_directory(path.clone());
+								notes_list_clone.borrow_mut().update_path(path).await;
+							},
+							None => notes_list_clone.borrow_mut().refresh().await,
+						}
+					}
+				));
+			})
+			.build();
+
+		self.app.add_action_entries([action_move_to]);
+	}
+
+	pub fn register_show_trash_action(&self) {
+		let action_show_trash = gio::ActionEntry::builder("show-trash")
+			.activate(move |app: &adw::Application, _action, _parameter| {
+				TrashView::new(app).present();
+			})
+			.build();
+
+		self.app.add_action_entries([action_show_trash]);
+	}
+
+	pub fn register_show_preferences_action(&self) {
+		let action_show_preferences = gio::ActionEntry::builder("show-preferences")
+			.activate(move |app: &adw::Application, _action, _parameter| {
+				PreferencesWindow::new(app).present();
+			})
+			.build();
+
+		self.app.add_action_entries([action_show_preferences]);
+	}
+
+	// applies the settings after they were changed in the preferences
+	pub fn register_apply_settings_action(&self) {
+		let editor_clone = self.editor.clone();
+		let notes_list_clone = self.notes_list.clone();
+		let directory_tree_clone = self.directory_tree.clone();
+		let action_apply_settings = gio::ActionEntry::builder("apply-settings")
+			.activate(move |_, _action, _parameter| {
+				let settings = Settings::get();
+				editor_clone.borrow().apply_settings(&settings);
+				directory_tree_clone.borrow().apply_settings(&settings);
+				directory_tree_clone.borrow().refresh_notebooks();
+				notes_list_clone.borrow().apply_settings(&settings);
+
+				let Some(root) = Directories::bb_root_directory() else {
+					return
+				};
+				if directory_tree_clone.borrow().path == root {
+					return
+				}
+
+				// the notes directory was relocated
+				MainContext::default().spawn_local(glib::clone!(
+					#[strong] editor_clone,
+					#[strong] notes_list_clone,
+					#[strong] directory_tree_clone,
+					async move {
+						if let Err(e) = editor_clone.borrow().flush_all() {
+							eprintln!("Could not save notes before switching directories: {:?}", e);
+						}
+						// rebuilding the indexes reads every note
+						let _ = gio::spawn_blocking(Directories::root_changed).await;
+
+						Directories::set_current_directory_path(&root);
+						directory_tree_clone.borrow().update_current_directory(root.clone());
+						directory_tree_clone.borrow_mut().update_path(root.clone());
+						notes_list_clone.borrow_mut().update_path(root).await;
+					}
+				));
+			})
+			.build();
+
+		self.app.add_action_entries([action_apply_settings]);
+	}
+
+	// the tree, the notes list and the tabs are rebuilt for the root of the
+	// notebook, with the folder and notes it was left with
+	pub fn register_switch_notebook_action(&self) {
+		let editor_clone = self.editor.clone();
+		let notes_list_clone = self.notes_list.clone();
+		let directory_tree_clone = self.directory_tree.clone();
+		let action_switch_notebook = gio::ActionEntry::builder("switch-notebook")
+			.parameter_type(Some(&String::static_variant_type()))
+			.activate(move |_, _action, parameter| {
+				let name = parameter
+					.expect("Could not get Parameter")
+					.get::<String>()
+					.expect("The variant nees to be of type `String`");
+				if name == Notebooks::active().name {
+					return
+				}
+
+				if let Err(e) = editor_clone.borrow().flush_all() {
+					eprintln!("Could not save notes before switching notebooks: {:?}", e);
+					directory_tree_clone.borrow().refresh_notebooks();
+					return
+				}
+				let notebook = match Notebooks::switch_to(&name) {
+					Ok(notebook) => notebook,
+					Err(e) => {
+						eprintln!("Could not switch to notebook ´{}´: {:?}", name, e);
+						directory_tree_clone.borrow().refresh_notebooks();
+						return
+					}
+				};
+				let Some(root) = notebook.root() else {
+					return
+				};
+
+				// closing the tabs below stores an empty list of open notes
+				let open_paths = Notes::open_paths();
+				let current_note = Notes::current_path().unwrap_or_default();
+				let current_directory = Directories::current_directory_path()
+					.filter(|path| path.is_dir() && path.starts_with(&root))
+					.unwrap_or(root.clone());
+
+				MainContext::default().spawn_local(glib::clone!(
+					#[strong] editor_clone,
+					#[strong] notes_list_clone,
+					#[strong] directory_tree_clone,
+					async move {
+						editor_view::close_all_tabs(&editor_clone);
+						// rebuilding the indexes reads every note
+						let _ = gio::spawn_blocking(Directories::root_changed).await;
+
+						Directories::set_current_directory_path(&current_directory);
+						directory_tree_clone.borrow().update_current_directory(current_directory.clone());
+						directory_tree_clone.borrow_mut().update_path(root);
+						directory_tree_clone.borrow().refresh_notebooks();
+						notes_list_clone.borrow().update_current_note(current_note.clone());
+						notes_list_clone.borrow_mut().update_path(current_directory).await;
+
+						editor_clone.borrow_mut().restore_tabs(open_paths, current_note.clone()).await;
+						Notes::set_open_paths(&editor_clone.borrow().open_paths());
+						if current_note.is_file() {
+							Notes::set_current_path(&current_note);
+						}
+					}
+				));
+			})
+			.build();
+
+		self.app.add_action_entries([action_switch_notebook]);
+	}
+
+	// a notebook is added for an existing folder and becomes the active one
+	pub fn register_new_notebook_action(&self) {
+		let directory_tree_clone = self.directory_tree.clone();
+		let action_new_notebook = gio::ActionEntry::builder("new-notebook")
+			.activate(move |app: &adw::Application, _action, _parameter| {
+				let dialog = gtk::FileDialog::builder()
+					.title("Choose the folder of the notebook")
+					.modal(true)
+					.build();
+
+				dialog.select_folder(
+					app.active_window().as_ref(),
+					gio::Cancellable::NONE,
+					glib::clone!(
+						#[weak] app,
+						#[strong] directory_tree_clone,
+						move |result| {
+							// the dialog was cancelled
+							let Some(path) = result.ok().and_then(|folder| folder.path()) else {
+								return
+							};
+							let folder_name = path
+								.file_name()
+								.map(|name| name.to_string_lossy().to_string())
+								.unwrap_or_default();
+
+							// named after the folder, with a number if the name is taken
+							let names: Vec<String> = Notebooks::list()
+								.into_iter()
+								.map(|notebook| notebook.name)
+								.collect();
+							let mut name = folder_name.clone();
+							let mut counter = 2;
+							while names.contains(&name) {
+								name = format!("{folder_name} {counter}");
+								counter += 1;
+							}
+
+							match Notebooks::add(&name, &path) {
+								Ok(notebook) => {
+									directory_tree_clone.borrow().refresh_notebooks();
+									app.activate_action("switch-notebook", Some(&notebook.name.to_variant()));
+								},
+								Err(e) => eprintln!("Could not add notebook: {:?}", e),
+							}
+						}
+					)
+				);
+			})
+			.build();
+
+		self.app.add_action_entries([action_new_notebook]);
+	}
+
+	pub fn register_undo_file_op_action(&self) {
+		let editor_clone = self.editor.clone();
+		let notes_list_clone = self.notes_list.clone();
+		let directory_tree_clone = self.directory_tree.clone();
+		let action_undo_file_op = gio::ActionEntry::builder("undo-file-op")
+			.activate(move |app: &adw::Application, _action, _parameter| {
+				MainContext::default().spawn_local(glib::clone!(
+					#[weak] app,
+					#[strong] editor_clone,
+					#[strong] directory_tree_clone,
+					#[strong] notes_list_clone,
+					async move {
+						// pending changes have to be on disk before files move
+						if let Err(e) = editor_clone.borrow().flush_all() {
+							eprintln!("Could not save notes before undoing: {:?}", e);
+							return
+						}
+
+						match Journal::undo().await {
+							Ok(Some(operation)) => {
+								file_operation_applied(
+									&operation,
+									&editor_clone,
+									&notes_list_clone,
+									&directory_tree_clone
+								).await;
+							},
+							Ok(None) => {},
+							Err(e) => Dialogue::new(&app).message(
+								"Undo",
+								"The last file operation could not be undone.",
+								&e.to_string()
+							),
+						}
+					}
+				));
+			})
+			.build();
+
+		self.app.add_action_entries([action_undo_file_op]);
+	}
+
+	pub fn register_redo_file_op_action(&self) {
+		let editor_clone = self.editor.clone();
+		let notes_list_clone = self.notes_list.clone();
+		let directory_tree_clone = self.directory_tree.clone();
+		let action_redo_file_op = gio::ActionEntry::builder("redo-file-op")
+			.activate(move |app: &adw::Application, _action, _parameter| {
+				MainContext::default().spawn_local(glib::clone!(
+					#[weak] app,
+					#[strong] editor_clone,
+					#[strong] directory_tree_clone,
+					#[strong] notes_list_clone,
+					async move {
+						// pending changes have to be on disk before files move
+						if let Err(e) = editor_clone.borrow().flush_all() {
+							eprintln!("Could not save notes before redoing: {:?}", e);
+							return
+						}
+
+						match Journal::redo().await {
+							Ok(Some(operation)) => {
+								file_operation_applied(
+									&operation,
+									&editor_clone,
+									&notes_list_clone,
+									&directory_tree_clone
+								).await;
+							},
+							Ok(None) => {},
+							Err(e) => Dialogue::new(&app).message(
+								"Redo",
+								"The last file operation could not be redone.",
+								&e.to_string()
+							),
+						}
+					}
+				));
+			})
+			.build();
+
+		self.app.add_action_entries([action_redo_file_op]);
+	}
+
+	pub fn register_sort_notes_action(&self) {
+		let notes_list_clone = self.notes_list.clone();
+		let action_sort_notes = gio::ActionEntry::builder("sort-notes")
+			.parameter_type(Some(&String::static_variant_type()))
+			.activate(move |_, _action, parameter| {
+				let value = parameter
+					.expect("Could not get Parameter")
+					.get::<String>()
+					.expect("The variant nees to be of type `String`");
+				let Some(sort_mode) = SortMode::from_config_value(&value) else {
+					return
+				};
+
+				MainContext::default().spawn_local(glib::clone!(
+					#[weak] notes_list_clone,
+					async move {
+						let path = notes_list_clone.borrow().path.clone();
+						if Notes::sort_mode(&path) == sort_mode {
+							return
+						}
+						Notes::set_sort_mode(&path, sort_mode);
+						notes_list_clone.borrow_mut().refresh().await;
+					}
+				));
+			})
+			.build();
+
+		self.app.add_action_entries([action_sort_notes]);
+	}
+
 	pub fn register_open_note_action(&self) {
 		let editor_clone = self.editor.clone();
 		let notes_list_clone = self.notes_list.clone();
 		let action_open_notes = gio::ActionEntry::builder("open-note")
+			.parameter_type(Some(&String::static_variant_type()))
+			.activate(move |app: &adw::Application, _action, parameter| {
+				let path = parameter
+					.expect("Could not get Parameter")
+					.get::<String>()
+					.expect("The variant nees to be of type `String`");
+				MainContext::default().spawn_local(glib::clone!(
+					#[weak] app, #[weak] notes_list_clone, #[weak] editor_clone, #[strong] path,
+					async move {
+						notes_list_clone.borrow_mut().update_current_note(path.clone().into());
+						let result = editor_clone.borrow_mut().update_path(path.into()).await;
+						// the unsaved note stays open in its own tab
+						if let Err(e) = result {
+							Dialogue::new(&app).message(
+								"Note Not Saved",
+								"The previous note could not be saved, it is kept open in its tab.",
+								&e.to_string()
+							);
+						}
+					}
+				));
+			})
+			.build();
+
+		self.app.add_action_entries([action_open_notes]);
+	}
+
+	pub fn register_open_tab_action(&self) {
+		let editor_clone = self.editor.clone();
+		let notes_list_clone = self.notes_list.clone();
+		let action_open_tab = gio::ActionEntry::builder("open-tab")
 			.parameter_type(Some(&String::static_variant_type()))
 			.activate(move |_, _action, parameter| {
 				let path = parameter
@@ -75,13 +524,32 @@ impl<'a> ActionEntries<'a> {
 					#[weak] notes_list_clone, #[weak] editor_clone, #[strong] path,
 					async move {
 						notes_list_clone.borrow_mut().update_current_note(path.clone().into());
-						editor_clone.borrow_mut().update_path(path.into()).await;
+						editor_clone.borrow_mut().open_in_new_tab(path.into()).await;
 					}
 				));
 			})
 			.build();
 
-		self.app.add_action_entries([action_open_notes]);
+		self.app.add_action_entries([action_open_tab]);
+	}
+
+	pub fn register_highlight_search_action(&self) {
+		let highlight_query = self.editor.borrow().highlight_query.clone();
+		let action_highlight_search = gio::ActionEntry::builder("highlight-search")
+			.parameter_type(Some(&String::static_variant_type()))
+			.activate(move |_, _action, parameter| {
+				let query = parameter
+					.expect("Could not get Parameter")
+					.get::<String>()
+					.expect("The variant nees to be of type `String`");
+				*highlight_query.borrow_mut() = match query.trim().is_empty() {
+					true => None,
+					false => Some(query)
+				};
+			})
+			.build();
+
+		self.app.add_action_entries([action_highlight_search]);
 	}
 
 	pub fn register_editor_key_up(&self) {
@@ -89,7 +557,7 @@ impl<'a> ActionEntries<'a> {
 		let action_editor_key_up = gio::ActionEntry::builder("editor-key-up")
 			.parameter_type(Some(&String::static_variant_type()))
 			.activate(move |_, _, _| {
-				editor_clone.borrow_mut().write_note();
+				editor_clone.borrow().schedule_save();
 			})
 			.build();
 
@@ -106,3 +574,37 @@ impl<'a> ActionEntries<'a> {
 		self.app.add_action_entries([action_create_note]);
 	}
 }
+
+// brings the tabs and panels in line with a file operation that was undone
+// or redone, tabs of moved notes follow them and those of trashed notes close
+async fn file_operation_applied(
+	operation: &Operation,
+	editor: &Rc<RefCell<Editor>>,
+	notes_list: &Rc<RefCell<NotesList>>,
+	directory_tree: &Rc<RefCell<DirectoryTree>>,
+) {
+	let moved_paths = match operation {
+		Operation::Rename { old_path, new_path, .. } => Some((old_path, new_path)),
+		Operation::Restore { trashed_path, path } => Some((trashed_path, path)),
+		Operation::Delete { path, .. } => {
+			editor_view::close_tabs_below(editor, path);
+			None
+		},
+		Operation::Create { .. } | Operation::SetPinned { .. } => None,
+	};
+	if let Some((old_path, new_path)) = moved_paths {
+		editor.borrow_mut().paths_moved(old_path, new_path).await;
+	}
+	directory_tree.borrow_mut().refresh();
+
+	// the listed folder might have moved as well
+	let notes_path = notes_list.borrow().path.clone();
+	match moved_paths.and_then(|(old_path, new_path)| editor_view::moved_path(&notes_path, old_path, new_path)) {
+		Some(path) => {
+			Directories::set_current_directory_path(&path);
+			directory_tree.borrow().update_current_directory(path.clone());
+			notes_list.borrow_mut().update_path(path).await;
+		},
+		None => notes_list.borrow_mut().refresh().await,
+	}
+}
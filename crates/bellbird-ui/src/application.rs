@@ -7,17 +7,23 @@ use gtk::prelude::*;
 use gtk::glib;
 
 use bellbird_core::directories::Directories;
-use bellbird_core::config::Config;
+use bellbird_core::config::{Config, Settings, PANEL_WIDTH_RANGE};
+use bellbird_core::notebooks::Notebooks;
 use bellbird_core::notes::Notes;
+use bellbird_core::vault::Vault;
 
 use crate::action_entries::ActionEntries;
 use crate::contextmenu::directory_tree_context_menu::DirectoryTreeContextMenu;
 use crate::contextmenu::notes_list_context_menu::NotesListContextMenu;
+use crate::dialogue::Dialogue;
 use crate::directory_tree::DirectoryTree;
 use crate::editor_view::Editor;
 use crate::notes_list::NotesList;
+use crate::search_panel::SearchPanel;
 use crate::directory_tree;
+use crate::file_watcher;
 use crate::editor_view;
+use crate::search_panel;
 use crate::default_layout;
 
 pub fn run() -> glib::ExitCode {
@@ -25,7 +31,18 @@ pub fn run() -> glib::ExitCode {
 	let app = adw::Application::builder()
 		.application_id(config.app_id()).build();
 
-	app.connect_startup(|_| load_css());
+	app.connect_startup(|_| {
+		load_css();
+		editor_view::register_syntax_highlighting();
+		// files might have been moved or deleted while the app was closed
+		let vault = Vault::current();
+		if let Err(e) = vault.config().migrate_meta_infos_to_ids(&vault) {
+			eprintln!("Could not migrate metainfos: {:?}", e);
+		}
+		if let Err(e) = Notebooks::collect_stale_meta_infos() {
+			eprintln!("Could not clean up metainfos: {:?}", e);
+		}
+	});
 	//app.connect_activate(build_ui);
 	app.connect_activate(|app| {
 		build_ui(&app);
@@ -43,15 +60,18 @@ fn build_ui(app: &adw::Application) {
 	let panels_wrapper = gtk::Box::builder()
 		.orientation(gtk::Orientation::Horizontal)
 		.name("panels-wrapper")
+		.hexpand(true)
 		.spacing(0)
 		.build();
 
 	let config = Config::new();
+	let settings = Settings::get();
 	let window = gtk::ApplicationWindow::new(app);
 
 	//window.set_titlebar(Some(&gtk::Box::new(gtk::Orientation::Horizontal, 0)));
 	window.set_title(Some(&config.app_name()));
-	window.set_default_size(1000, 600);
+	window.set_default_size(settings.window_width, settings.window_height);
+	window.set_maximized(settings.window_maximized);
 	window.set_child(Some(&window_box));
 
 	let bellbird_root = match Directories::bb_root_directory() {
@@ -74,6 +94,7 @@ fn build_ui(app: &adw::Application) {
 	));
 	let notes_list = Rc::new(RefCell::new(NotesList::new(&path)));
 	let editor = Rc::new(RefCell::new(Editor::new(&note_path)));
+	let search_panel = Rc::new(RefCell::new(SearchPanel::new()));
 
 	directory_tree.borrow_mut().update_current_directory(path.clone().into());
 	directory_tree.borrow_mut().update_path(bellbird_root.to_path_buf());
@@ -83,20 +104,96 @@ fn build_ui(app: &adw::Application) {
 		#[weak] notes_list, #[strong] note_path, #[weak] editor,
 		async move {
 			notes_list.borrow_mut().update_path(path.to_path_buf().into()).await;
-			editor.borrow_mut().update_path(note_path.to_path_buf()).await;
+			editor.borrow_mut().restore_tabs(Notes::open_paths(), note_path.to_path_buf()).await;
 		}
 	));
 
 	register_actions(&app, &directory_tree, &notes_list, &editor);
+	file_watcher::watch(&app, &bellbird_root, &directory_tree, &notes_list, &editor);
 
-	panels_wrapper.append(&directory_tree::build_ui(&app, &directory_tree));
-	panels_wrapper.append(&notes_list.borrow_mut().build_ui(&app));
-	panels_wrapper.append(&editor_view::build_ui(&editor));
+	// the sidebar and the notes list can be resized by dragging the
+	// handle between them and the panel to their right
+	let notes_list_paned = gtk::Paned::builder()
+		.orientation(gtk::Orientation::Horizontal)
+		.start_child(&notes_list.borrow_mut().build_ui(&app))
+		.end_child(&editor_view::build_ui(&editor))
+		.resize_start_child(false)
+		.shrink_start_child(false)
+		.position(settings.notes_list_width)
+		.hexpand(true)
+		.build();
+
+	panels_wrapper.append(&search_panel::build_ui(&search_panel));
+	panels_wrapper.append(&notes_list_paned);
+
+	let sidebar_paned = gtk::Paned::builder()
+		.orientation(gtk::Orientation::Horizontal)
+		.start_child(&directory_tree::build_ui(&app, &directory_tree))
+		.end_child(&panels_wrapper)
+		.resize_start_child(false)
+		.shrink_start_child(false)
+		.position(settings.sidebar_width)
+		.vexpand(true)
+		.build();
 
-	window_box.append(&panels_wrapper);
+	// pending changes are written before the window goes away, if that
+	// fails the user gets to decide whether to close anyway
+	window.connect_close_request(glib::clone!(
+		#[weak] app, #[strong] editor, #[weak] sidebar_paned, #[weak] notes_list_paned,
+		#[upgrade_or] glib::Propagation::Proceed,
+		move |window| {
+			store_layout(window, &sidebar_paned, &notes_list_paned);
+
+			let Err(e) = editor.borrow().flush_all() else {
+				return glib::Propagation::Proceed
+			};
+
+			let window = window.clone();
+			Dialogue::new(&app).warning_yes_no(
+				"Unsaved Changes",
+				"Not every note could be saved. Close anyway?",
+				&e.to_string(),
+				move || window.destroy(),
+				|| {}
+			);
+			glib::Propagation::Stop
+		}
+	));
+
+	window_box.append(&sidebar_paned);
 	window.present();
 }
 
+// the window size and the pane positions are restored on the next start
+fn store_layout(
+	window: &gtk::ApplicationWindow,
+	sidebar_paned: &gtk::Paned,
+	notes_list_paned: &gtk::Paned,
+) {
+	let mut settings = Settings::get();
+	settings.window_maximized = window.is_maximized();
+	if !settings.window_maximized {
+		(settings.window_width, settings.window_height) = window.default_size();
+	}
+
+	// hidden panels keep the width they had
+	let pane_width = |paned: &gtk::Paned| {
+		paned.start_child()
+			.is_some_and(|child| child.is_visible())
+			.then(|| paned.position().clamp(*PANEL_WIDTH_RANGE.start(), *PANEL_WIDTH_RANGE.end()))
+	};
+	if let Some(width) = pane_width(sidebar_paned) {
+		settings.sidebar_width = width;
+	}
+	if let Some(width) = pane_width(notes_list_paned) {
+		settings.notes_list_width = width;
+	}
+
+	if let Err(e) = settings.save() {
+		eprintln!("Could not save the window layout: {:?}", e);
+	}
+}
+
 fn load_css() {
 	let provider = gtk::CssProvider::new();
 	provider.load_from_string(default_layout::DEFAULT_STYLE);
@@ -114,10 +211,10 @@ fn register_actions(
 	notes_list: &Rc<RefCell<NotesList>>,
 	editor: &Rc<RefCell<Editor>>,
 ) {
-	Arc::new(NotesListContextMenu::new(app, notes_list.clone()))
+	Arc::new(NotesListContextMenu::new(app, notes_list.clone(), editor.clone()))
 		.setup_context_menu_actions();
 
-	Arc::new(DirectoryTreeContextMenu::new(app, directory_tree.clone()))
+	Arc::new(DirectoryTreeContextMenu::new(app, directory_tree.clone(), notes_list.clone(), editor.clone()))
 		.setup_context_menu_actions();
 
 	let action_entries = ActionEntries::new(
@@ -127,6 +224,25 @@ fn register_actions(
 		&editor,
 	);
 	action_entries.register_refresh_notes_action();
+	action_entries.register_refresh_directories_action();
+	action_entries.register_move_to_action();
+	action_entries.register_filter_tag_action();
+	action_entries.register_refresh_tags_action();
+	action_entries.register_show_trash_action();
+	action_entries.register_show_preferences_action();
+	action_entries.register_apply_settings_action();
+	action_entries.register_switch_notebook_action();
+	action_entries.register_new_notebook_action();
+	action_entries.register_sort_notes_action();
 	action_entries.register_open_note_action();
+	action_entries.register_open_tab_action();
+	action_entries.register_highlight_search_action();
 	action_entries.register_editor_key_up();
+	action_entries.register_undo_file_op_action();
+	action_entries.register_redo_file_op_action();
+
+	// plain ctrl+z stays with the editor's own undo stack
+	app.set_accels_for_action("app.undo-file-op", &["<Control><Alt>z"]);
+	app.set_accels_for_action("app.redo-file-op", &["<Control><Alt><Shift>z"]);
+	app.set_accels_for_action("app.show-preferences", &["<Control>comma"]);
 }
@@ -0,0 +1,171 @@
+use std::cell::{Cell, RefCell};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use bellbird_core::directories::Directories;
+use bellbird_core::links::Links;
+use glib::MainContext;
+use gtk::gio;
+use gtk::prelude::*;
+
+use crate::editor_view::{self, Editor};
+
+#[derive(Debug, Clone)]
+pub struct BacklinksPanel {
+	editor: Rc<RefCell<Editor>>,
+	revealer: gtk::Revealer,
+	list_box: gtk::ListBox,
+	backlinks: Rc<RefCell<Vec<PathBuf>>>,
+	// bumped on every refresh so results for a previous note get dropped
+	generation: Rc<Cell<u32>>,
+}
+
+impl BacklinksPanel {
+	pub fn new(editor: &Rc<RefCell<Editor>>) -> Self {
+		let list_box = gtk::ListBox::builder()
+			.selection_mode(gtk::SelectionMode::None)
+			.activate_on_single_click(true)
+			.css_classes(["backlinks-list"])
+			.build();
+
+		let placeholder = gtk::Label::builder()
+			.label("No notes link here")
+			.margin_top(20)
+			.margin_bottom(20)
+			.build();
+		list_box.set_placeholder(Some(&placeholder));
+
+		let revealer = gtk::Revealer::builder()
+			.transition_type(gtk::RevealerTransitionType::SlideLeft)
+			.reveal_child(false)
+			.build();
+
+		let backlinks_panel = Self {
+			editor: editor.clone(),
+			revealer,
+			list_box,
+			backlinks: Rc::new(RefCell::new(vec![])),
+			generation: Rc::new(Cell::new(0)),
+		};
+		backlinks_panel.build_ui();
+		backlinks_panel.connect_signals();
+		backlinks_panel
+	}
+
+	fn build_ui(&self) {
+		let panel = gtk::Box::builder()
+			.orientation(gtk::Orientation::Vertical)
+			.width_request(240)
+			.spacing(5)
+			.margin_start(5)
+			.margin_end(5)
+			.css_classes(["backlinks-panel"])
+			.build();
+
+		let label = gtk::Label::builder()
+			.label("Backlinks")
+			.halign(gtk::Align::Start)
+			.margin_top(5)
+			.build();
+
+		let scrollable_window = gtk::ScrolledWindow::builder()
+			.child(&self.list_box)
+			.hscrollbar_policy(gtk::PolicyType::Never)
+			.vexpand(true)
+			.build();
+
+		panel.append(&label);
+		panel.append(&scrollable_window);
+		self.revealer.set_child(Some(&panel));
+	}
+
+	fn connect_signals(&self) {
+		let self_clone = self.clone();
+		self.list_box.connect_row_activated(move |list_box, row| {
+			if let Some(path) = self_clone.backlinks.borrow().get(row.index() as usize) {
+				editor_view::open_note(list_box, path);
+			}
+		});
+
+		// a different note was opened in the editor
+		let self_clone = self.clone();
+		let editor_view = self.editor.borrow().view().clone();
+		editor_view.connect_buffer_notify(move |_| {
+			if self_clone.revealer.reveals_child() {
+				let self_clone = self_clone.clone();
+				glib::idle_add_local_once(move || self_clone.populate());
+			}
+		});
+	}
+
+	pub fn widget(&self) -> &gtk::Revealer {
+		&self.revealer
+	}
+
+	pub fn set_revealed(&self, revealed: bool) {
+		if revealed {
+			self.populate();
+		}
+		self.revealer.set_reveal_child(revealed);
+	}
+
+	fn populate(&self) {
+		let Ok(path) = self.editor.try_borrow().map(|editor| editor.path.clone()) else {
+			return
+		};
+		self.generation.set(self.generation.get() + 1);
+		let current_generation = self.generation.get();
+
+		let self_clone = self.clone();
+		MainContext::default().spawn_local(async move {
+			// the first lookup builds the link graph, keep it off the main thread
+			let backlinks = gio::spawn_blocking(move || Links::backlinks(&path))
+				.await
+				.unwrap_or_default();
+
+			if self_clone.generation.get() != current_generation {
+				return
+			}
+
+			self_clone.list_box.remove_all();
+			for backlink in backlinks.iter() {
+				self_clone.list_box.append(&self_clone.row(backlink));
+			}
+			*self_clone.backlinks.borrow_mut() = backlinks;
+		});
+	}
+
+	fn row(&self, path: &PathBuf) -> gtk::Box {
+		let row = gtk::Box::builder()
+			.orientation(gtk::Orientation::Vertical)
+			.margin_top(4)
+			.margin_bottom(4)
+			.margin_start(5)
+			.build();
+
+		let name = gtk::Label::builder()
+			.label(path.file_stem().unwrap_or_default().to_string_lossy().to_string())
+			.halign(gtk::Align::Start)
+			.ellipsize(gtk::pango::EllipsizeMode::End)
+			.css_classes(["backlink-name"])
+			.build();
+
+		let bellbird_root = Directories::bb_root_directory().unwrap_or_default();
+		let directory = path.parent()
+			.and_then(|parent| parent.strip_prefix(&bellbird_root).ok())
+			.map(|parent| parent.display().to_string())
+			.unwrap_or_default();
+		let details = gtk::Label::builder()
+			.label(&directory)
+			.halign(gtk::Align::Start)
+			.ellipsize(gtk::pango::EllipsizeMode::Middle)
+			.css_classes(["details"])
+			.build();
+
+		row.append(&name);
+		if !directory.is_empty() {
+			row.append(&details);
+		}
+		row
+	}
+}
@@ -12,6 +12,8 @@ pub struct Breadcrumb {
 	pub note_icon: TemplateChild<gtk::Image>,
 	#[template_child]
 	pub note_name: TemplateChild<gtk::Label>,
+	#[template_child]
+	pub save_state: TemplateChild<gtk::Label>,
 }
 
 #[glib::object_subclass]
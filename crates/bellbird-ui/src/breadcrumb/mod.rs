@@ -3,7 +3,14 @@ pub(crate) mod imp;
 use std::path::Path;
 
 use bellbird_core::directories::Directories;
-use gtk::{glib, subclass::prelude::*};
+use gtk::{glib, prelude::*, subclass::prelude::*};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SaveState {
+	Saved,
+	Unsaved,
+	Failed(String),
+}
 
 glib::wrapper! {
 	pub struct Breadcrumb(ObjectSubclass<imp::Breadcrumb>)
@@ -32,6 +39,22 @@ impl Breadcrumb {
 		imp.note_name.set_text(&self.get_note_name(path));
 	}
 
+	pub(crate) fn set_save_state(&self, state: &SaveState) {
+		let label = &self.imp().save_state;
+		for class in ["saved", "unsaved", "save-failed"] {
+			label.remove_css_class(class);
+		}
+
+		let (text, class, tooltip) = match state {
+			SaveState::Saved => ("Saved", "saved", None),
+			SaveState::Unsaved => ("Edited", "unsaved", None),
+			SaveState::Failed(error) => ("Not saved", "save-failed", Some(error.as_str())),
+		};
+		label.set_text(text);
+		label.add_css_class(class);
+		label.set_tooltip_text(tooltip);
+	}
+
 	fn get_note_name(&self, path: &Path) -> String {
 		let mut note = String::new();
 		if path.is_file() {
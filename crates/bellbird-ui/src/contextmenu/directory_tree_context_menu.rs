@@ -4,6 +4,8 @@ use bellbird_core::directories::Directories;
 use glib::MainContext;
 use gtk::{gio, prelude::*};
 
+use crate::editor_view::{self, Editor};
+use crate::notes_list::NotesList;
 use crate::{dialogue::Dialogue, directory_tree::DirectoryTree};
 
 
@@ -12,16 +14,22 @@ pub struct DirectoryTreeContextMenu {
 	app: adw::Application,
 	directory_tree
 	: Rc<RefCell<DirectoryTree>>,
+	notes_list: Rc<RefCell<NotesList>>,
+	editor: Rc<RefCell<Editor>>,
 }
 
 impl DirectoryTreeContextMenu {
 	pub fn new(
 		app: &adw::Application,
-		directory_tree: Rc<RefCell<DirectoryTree>>
+		directory_tree: Rc<RefCell<DirectoryTree>>,
+		notes_list: Rc<RefCell<NotesList>>,
+		editor: Rc<RefCell<Editor>>
 	) -> Self {
 		Self {
 			app: app.clone(),
-			directory_tree
+			directory_tree,
+			notes_list,
+			editor
 		}
 	}
 
@@ -41,11 +49,17 @@ impl DirectoryTreeContextMenu {
 		app_clone.add_action(&create_folder);
 
 		let duplicate_folder = gio::SimpleAction::new("duplicate-folder", None);
-		duplicate_folder.connect_activate(move |_, _| println!("duplicate folder"));
+		{
+			let self_clone = Arc::clone(&self);
+			duplicate_folder.connect_activate(move |_, _| self_clone.duplicate_folder());
+		}
 		app_clone.add_action(&duplicate_folder);
 
 		let pin_folder = gio::SimpleAction::new("toggle-pin-folder", None);
-		pin_folder.connect_activate(move |_, _| println!("pin folder"));
+		{
+			let self_clone = Arc::clone(&self);
+			pin_folder.connect_activate(move |_, _| self_clone.toggle_pin_folder());
+		}
 		app_clone.add_action(&pin_folder);
 
 		let rename_folder = gio::SimpleAction::new("rename-folder", None);
@@ -91,8 +105,25 @@ impl DirectoryTreeContextMenu {
 		)
 	}
 
+	fn duplicate_folder(&self) {
+		let path = self.directory_tree.borrow().selected_ctx_path.borrow().clone();
+		if let Err(e) = Directories::duplicate(&path) {
+			eprintln!("Could not duplicate folder: {:?}", e);
+		}
+		self.directory_tree.borrow_mut().refresh();
+	}
+
+	fn toggle_pin_folder(&self) {
+		let path = self.directory_tree.borrow().selected_ctx_path.borrow().clone();
+		Directories::set_is_pinned(&path, !Directories::is_pinned(&path));
+		self.directory_tree.borrow_mut().refresh();
+	}
+
 	async fn rename_folder(&self) {
+		let app_clone = self.app.clone();
 		let directory_tree_clone = self.directory_tree.clone();
+		let notes_list_clone = self.notes_list.clone();
+		let editor_clone = self.editor.clone();
 		let dialogue = Dialogue::new(&self.app);
 		let pathbuf_rc = self.directory_tree.borrow_mut().selected_ctx_path.clone();
 		let (full_path, _, file_stem) = self.get_path_and_stem(&pathbuf_rc).await;
@@ -101,12 +132,44 @@ impl DirectoryTreeContextMenu {
 			&format!("Rename ´{}´ to:", file_stem),
 			&file_stem,
 			move |folder| {
-				let mut new_path = PathBuf::from(directory_tree_clone.borrow_mut()
-					                           .path.to_str().unwrap_or(""));
-				new_path.push(&folder);
+				// nested folders are renamed in place
 				let old_path = PathBuf::from(&full_path);
-				let _ = Directories::rename(&old_path, &new_path);
-				directory_tree_clone.borrow_mut().refresh();
+				let mut new_path = match old_path.parent() {
+					Some(parent) => parent.to_path_buf(),
+					None => PathBuf::from(directory_tree_clone.borrow_mut()
+						                    .path.to_str().unwrap_or(""))
+				};
+				new_path.push(&folder);
+
+				MainContext::default().spawn_local(glib::clone!(
+					#[strong] app_clone, #[strong] directory_tree_clone, #[strong] notes_list_clone, #[strong] editor_clone,
+					async move {
+						// pending changes have to be on disk before the notes move
+						if let Err(e) = editor_clone.borrow().flush_all() {
+							eprintln!("Could not save notes before renaming: {:?}", e);
+							return
+						}
+						if let Err(e) = Directories::rename(&old_path, &new_path) {
+							Dialogue::new(&app_clone).message(
+								"Rename Folder",
+								&format!("Could not rename ´{}´.", old_path.file_name().unwrap_or_default().to_string_lossy()),
+								&e.to_string()
+							);
+							return
+						}
+
+						editor_clone.borrow_mut().paths_moved(&old_path, &new_path).await;
+						directory_tree_clone.borrow_mut().refresh();
+
+						// the listed folder might have been renamed as well
+						let notes_path = notes_list_clone.borrow().path.clone();
+						if let Some(path) = editor_view::moved_path(&notes_path, &old_path, &new_path) {
+							Directories::set_current_directory_path(&path);
+							directory_tree_clone.borrow().update_current_directory(path.clone());
+							notes_list_clone.borrow_mut().update_path(path).await;
+						}
+					}
+				));
 			},
 			|| {}
 		)
@@ -117,16 +180,38 @@ impl DirectoryTreeContextMenu {
 		// but works for now
 		let app_clone = self.app.clone();
 		let directory_tree_clone = self.directory_tree.clone();
+		let notes_list_clone = self.notes_list.clone();
+		let editor_clone = self.editor.clone();
 		let dialogue = Dialogue::new(&app_clone);
 		let pathbuf_rc = self.directory_tree.borrow_mut().selected_ctx_path.clone();
 		let (full_path, directory_path, _) = self.get_path_and_stem(&pathbuf_rc).await;
 		dialogue.warning_yes_no(
 			"Delete Folder",
-			"Do you really want to delete this folder?\n(Note: its content will also be deleted)",
+			"Do you really want to move this folder to the trash?\n(Note: its content will be moved as well)",
 			&format!("´{}´", directory_path),
 			move || {
-				let _ = Directories::delete(&PathBuf::from(&full_path), true);
+				let path = PathBuf::from(&full_path);
+				// closing saves the notes, a pending save would bring them back
+				editor_view::close_tabs_below(&editor_clone, &path);
+				if let Err(e) = Directories::delete(&path, true) {
+					eprintln!("Could not delete folder: {:?}", e);
+					return
+				}
 				directory_tree_clone.borrow_mut().refresh();
+
+				// the listed folder went to the trash along with it
+				let notes_path = notes_list_clone.borrow().path.clone();
+				if let (true, Some(parent)) = (notes_path.starts_with(&path), path.parent()) {
+					let parent = parent.to_path_buf();
+					Directories::set_current_directory_path(&parent);
+					directory_tree_clone.borrow().update_current_directory(parent.clone());
+					MainContext::default().spawn_local(glib::clone!(
+						#[strong] notes_list_clone,
+						async move {
+							notes_list_clone.borrow_mut().update_path(parent).await;
+						}
+					));
+				}
 			},
 			|| {}
 		)
@@ -1,26 +1,31 @@
 use std::{cell::RefCell, rc::Rc, sync::Arc};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use bellbird_core::links::Links;
 use bellbird_core::notes::Notes;
 use glib::MainContext;
 use gtk::{gio, prelude::*};
 
+use crate::editor_view::{self, Editor};
 use crate::{dialogue::Dialogue, notes_list::NotesList};
 
 #[derive(Debug, Clone)]
 pub struct NotesListContextMenu {
 	app: adw::Application,
 	notes_list: Rc<RefCell<NotesList>>,
+	editor: Rc<RefCell<Editor>>,
 }
 
 impl NotesListContextMenu {
 	pub fn new(
 		app: &adw::Application,
-		notes_list: Rc<RefCell<NotesList>>
+		notes_list: Rc<RefCell<NotesList>>,
+		editor: Rc<RefCell<Editor>>
 	) -> Self {
 		Self {
 			app: app.clone(),
-			notes_list
+			notes_list,
+			editor
 		}
 	}
 
@@ -28,7 +33,10 @@ impl NotesListContextMenu {
 		let app_clone = self.app.clone();
 
 		let open_in_tab = gio::SimpleAction::new("open-note-in-tab", None);
-		open_in_tab.connect_activate(move |_, _| println!("open in tab"));
+		{
+			let self_clone = Arc::clone(&self);
+			open_in_tab.connect_activate(move |_, _| self_clone.open_note_in_tab());
+		}
 		app_clone.add_action(&open_in_tab);
 
 		// @todo try to make this whole thing less verbose
@@ -40,7 +48,15 @@ impl NotesListContextMenu {
 		app_clone.add_action(&create_note);
 
 		let duplicate_note = gio::SimpleAction::new("duplicate-note", None);
-		duplicate_note.connect_activate(move |_, _| println!("duplicate note"));
+		{
+			let self_clone = Arc::clone(&self);
+			duplicate_note.connect_activate(move |_, _| {
+				MainContext::default().spawn_local(glib::clone!(
+					#[weak] self_clone,
+					async move { self_clone.duplicate_note().await; }
+				));
+			});
+		}
 		app_clone.add_action(&duplicate_note);
 
 		let pin_note = gio::SimpleAction::new("toggle-pin-note", None);
@@ -75,6 +91,12 @@ impl NotesListContextMenu {
 		app_clone.add_action(&delete_note);
 	}
 
+	fn open_note_in_tab(&self) {
+		let path = self.notes_list.borrow().selected_ctx_path.borrow().display().to_string();
+		Notes::set_current_path(Path::new(&path));
+		self.app.activate_action("open-tab", Some(&path.to_variant()));
+	}
+
 	fn create_note(&self) {
 		let notes_list_clone = self.notes_list.clone();
 		let dialogue = Dialogue::new(&self.app);
@@ -89,7 +111,9 @@ impl NotesListContextMenu {
 						let mut path = PathBuf::from(notes_list_clone.borrow_mut()
 							.path.to_str().unwrap_or(""));
 						path.push(&note);
-						let _ = Notes::write_to_file(path, String::new());
+						if let Err(e) = Notes::create(&path) {
+							eprintln!("Could not create note: {:?}", e);
+						}
 						notes_list_clone.borrow_mut().refresh().await;
 					}
 				));
@@ -98,8 +122,19 @@ impl NotesListContextMenu {
 		)
 	}
 
+	async fn duplicate_note(&self) {
+		let path = self.notes_list.borrow().selected_ctx_path.borrow().clone();
+		if let Err(e) = Notes::duplicate(&path) {
+			eprintln!("Could not duplicate note: {:?}", e);
+		}
+		let mut notes_list = self.notes_list.borrow().clone();
+		notes_list.refresh().await;
+	}
+
 	async fn rename_note(&self) {
+		let app_clone = self.app.clone();
 		let notes_list_clone = self.notes_list.clone();
+		let editor_clone = self.editor.clone();
 		let dialogue = Dialogue::new(&self.app);
 		let pathbuf_rc = self.notes_list.borrow_mut().selected_ctx_path.clone();
 		let (note_path, file_stem) = self.get_path_and_stem(&pathbuf_rc);
@@ -110,7 +145,7 @@ impl NotesListContextMenu {
 			&file_stem,
 			move |note| {
 				MainContext::default().spawn_local(glib::clone!(
-					#[strong] note_path, #[strong] notes_list_clone,
+					#[strong] note_path, #[strong] notes_list_clone, #[strong] app_clone, #[strong] editor_clone,
 					async move {
 						let mut new_path = PathBuf::from(
 							notes_list_clone.borrow_mut()
@@ -118,9 +153,26 @@ impl NotesListContextMenu {
 								.unwrap_or("")
 						);
 						new_path.push(&note);
+						let new_path = Notes::ensure_correct_path(&new_path);
+
+						// pending changes have to be on disk before the file moves
+						if let Err(e) = editor_clone.borrow().flush_all() {
+							eprintln!("Could not save notes before renaming: {:?}", e);
+							return
+						}
 
 						let old_path = PathBuf::from(&note_path);
-						let _f = Notes::rename(old_path, new_path).await;
+						match Notes::rename(old_path.clone(), new_path.clone()).await {
+							Ok(_) => {
+								editor_clone.borrow_mut().paths_moved(&old_path, &new_path).await;
+								Self::offer_link_rewrite(&app_clone, &editor_clone, &old_path, &new_path);
+							},
+							Err(e) => Dialogue::new(&app_clone).message(
+								"Rename Note",
+								&format!("Could not rename ´{}´.", old_path.file_stem().unwrap_or_default().to_string_lossy()),
+								&e.to_string()
+							),
+						}
 						notes_list_clone.borrow_mut().refresh().await;
 					}
 				));
@@ -129,6 +181,43 @@ impl NotesListContextMenu {
 		)
 	}
 
+	// notes linking to the renamed note by its old name can be updated
+	fn offer_link_rewrite(
+		app: &adw::Application,
+		editor: &Rc<RefCell<Editor>>,
+		old_path: &Path,
+		new_path: &Path
+	) {
+		let backlinks = Links::backlinks(old_path);
+		if backlinks.is_empty() {
+			return
+		}
+
+		let old_name = old_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+		let new_name = new_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+		let (old_path, new_path) = (old_path.to_path_buf(), new_path.to_path_buf());
+		let editor = editor.clone();
+
+		Dialogue::new(app).warning_yes_no(
+			"Update Links",
+			&format!("{} note(s) link to ´{}´.", backlinks.len(), old_name),
+			&format!("Change those links to point to ´{}´?", new_name),
+			move || {
+				// open notes are saved first and loaded again afterwards,
+				// otherwise their next save would undo the new links
+				if let Err(e) = editor.borrow().flush_all() {
+					eprintln!("Could not save notes before updating links: {:?}", e);
+					return
+				}
+				match Links::rewrite_links(&old_path, &new_path) {
+					Ok(rewritten) => editor.borrow().reload_notes(&rewritten),
+					Err(e) => eprintln!("Could not update links: {:?}", e),
+				}
+			},
+			|| {}
+		)
+	}
+
 	async fn toggle_pin_note(&self) {
 		let notes_list_clone = self.notes_list.clone();
 		let binding = notes_list_clone.borrow_mut();
@@ -143,19 +232,23 @@ impl NotesListContextMenu {
 		// but works for now
 		let app_clone = self.app.clone();
 		let notes_list_clone = self.notes_list.clone();
+		let editor_clone = self.editor.clone();
 		let dialogue = Dialogue::new(&app_clone);
 		let pathbuf_rc = self.notes_list.borrow_mut().selected_ctx_path.clone();
 		let (note_path, file_stem) = self.get_path_and_stem(&pathbuf_rc);
 
 		dialogue.warning_yes_no(
 			"Delete New Note",
-			"Do you really want to delete this note?",
+			"Do you really want to move this note to the trash?",
 			&format!("´{}´", file_stem),
 			move || {
 				MainContext::default().spawn_local(glib::clone!(
-					#[weak] notes_list_clone, #[strong] note_path,
+					#[weak] notes_list_clone, #[weak] editor_clone, #[strong] note_path,
 					async move {
-						Notes::delete(&PathBuf::from(&note_path)).await;
+						let note_path = PathBuf::from(&note_path);
+						// a pending save would bring the note back
+						editor_view::close_tabs_below(&editor_clone, &note_path);
+						Notes::delete(&note_path).await;
 						notes_list_clone.borrow_mut().refresh().await;
 					})
 				);
@@ -44,6 +44,7 @@ headerbar {
 }
 
 .directories-panel > label,
+.directories-panel .pinned-directories > label,
 windowhandle label {
 	text-transform: uppercase;
 	font-weight: 700;
@@ -61,6 +62,25 @@ windowhandle label {
 }
 
 .directories-panel row { border-radius: 4px; }
+.directories-panel row:drop(active) { box-shadow: inset 0 0 0 1px #A5A1BC; }
+.directories-panel .trash-button { color: #777; }
+.directories-panel .notebook-switcher button { color: #777; }
+
+.directories-panel .pinned-directories-list,
+.directories-panel .tags-list {
+	background-color: transparent;
+	padding: 0 10px;
+}
+.directories-panel .tags-list .details {
+	color: #999;
+	font-size: 11px;
+}
+
+.trash-window .trash-name { font-weight: 700; }
+.trash-window .details {
+	color: #999;
+	font-size: 11px;
+}
 .directories-panel treeexpander.hide { opacity: 0; }
 
 .notes-panel {
@@ -72,7 +92,7 @@ windowhandle label {
 
 .notes-panel listview { padding: 0 5px; }
 
-.notes-panel > box > label {
+.notes-panel .panel-header > label {
 	text-transform: uppercase;
 	font-weight: 700;
 	color: #A5A1BC;
@@ -87,10 +107,51 @@ windowhandle label {
 	filter: invert(100%) opacity(25%);
 }
 
+.notes-panel .sort-dropdown button {
+	min-height: 0;
+	padding: 2px 6px;
+	font-size: 11px;
+	color: #999;
+}
+
+.notes-panel .details {
+	color: #999;
+	font-size: 11px;
+}
+
+.notes-panel .preview {
+	color: #777;
+	font-size: 11px;
+}
+
 .notes-panel row:last-child {
 	border-bottom-color: transparent;
 }
 
+.search-panel {
+	border-radius: 4px;
+	background-color: #fff;
+	border: 1px solid #EDEDED;
+}
+
+.search-panel listview { padding: 0 5px; }
+
+.search-panel > label {
+	text-transform: uppercase;
+	font-weight: 700;
+	color: #A5A1BC;
+}
+
+.search-panel row {
+	border-radius: 4px;
+	border-bottom-color: #eee;
+}
+
+.search-panel .snippet {
+	color: #888;
+	font-size: 11px;
+}
+
 .editor-panel {
 	border-radius: 4px;
 	background-color: #fff;
@@ -101,6 +162,38 @@ windowhandle label {
 	line-height: 1.2;
 }
 
+.editor-panel .editor-tabs {
+	background-color: transparent;
+	border-bottom: 1px solid #EDEDED;
+}
+.editor-panel .editor-tabs tab { font-size: 12px; }
+
+.editor-panel .history-button,
+.editor-panel .preview-button,
+.editor-panel .backlinks-button,
+.editor-panel .preferences-button { color: #999; }
+.editor-panel .markdown-preview-panel { border-left: 1px solid #EDEDED; }
+.editor-panel .history-panel {
+	border-left: 1px solid #EDEDED;
+	padding-left: 5px;
+}
+.editor-panel .backlinks-panel {
+	border-left: 1px solid #EDEDED;
+	padding-left: 5px;
+}
+.editor-panel .backlinks-panel .backlink-name { font-weight: 700; }
+.editor-panel .backlinks-panel .details {
+	color: #999;
+	font-size: 11px;
+}
+.editor-panel .backlinks-panel > label,
+.editor-panel .history-panel > label {
+	text-transform: uppercase;
+	font-weight: 700;
+	color: #A5A1BC;
+}
+.editor-panel .history-diff { font-size: 12px; }
+
 #breadcrumb {
 	color: #999;
 	font-size: 12px;
@@ -114,6 +207,13 @@ windowhandle label {
 	font-weight: 700;
 }
 
+#breadcrumb .save-state { font-size: 11px; }
+#breadcrumb .save-state.unsaved { color: #B5B1CC; }
+#breadcrumb .save-state.save-failed {
+	color: #C01C28;
+	font-weight: 700;
+}
+
 .status-bar {
 	background-color: #E4E5E7;
 }
@@ -92,6 +92,75 @@ impl<'a> Dialogue<'a> {
 		self.window.present();
 	}
 
+	// only an Ok button, e.g. to tell about something that went wrong
+	pub fn message(&self, title: &str, label: &str, description: &str) {
+		self.create_window(title, 300, 0);
+
+		let window_box = gtk::Box::builder()
+			.orientation(gtk::Orientation::Vertical)
+			.margin_start(15)
+			.margin_end(15)
+			.margin_top(10)
+			.margin_bottom(10)
+			.build();
+
+		let label = self.label(label);
+		let description = self.label(description);
+		let button_box = self.button_box();
+
+		button_box.append(&self.ok_button(|| {}));
+
+		window_box.append(&label);
+		window_box.append(&description);
+		window_box.append(&button_box);
+
+		self.key_events(&window_box);
+
+		self.window.set_child(Some(&window_box));
+		self.window.present();
+	}
+
+	// like `warning_yes_no`, but with a button for each of `choices`
+	pub fn warning_choice<C>(
+		&self,
+		title: &str,
+		label: &str,
+		description: &str,
+		choices: Vec<(&str, Box<dyn Fn()>)>,
+		cancel: C
+	)
+	where
+		C: Fn() + 'static
+	{
+		self.create_window(title, 300, 0);
+
+		let window_box = gtk::Box::builder()
+			.orientation(gtk::Orientation::Vertical)
+			.margin_start(15)
+			.margin_end(15)
+			.margin_top(10)
+			.margin_bottom(10)
+			.build();
+
+		let label = self.label(label);
+		let description = self.label(description);
+		let button_box = self.button_box();
+
+		for (choice_label, choice) in choices {
+			button_box.append(&self.choice_button(choice_label, choice));
+		}
+		button_box.append(&self.cancel_button(move || cancel()));
+
+		window_box.append(&label);
+		window_box.append(&description);
+		window_box.append(&button_box);
+
+		self.key_events(&window_box);
+
+		self.window.set_child(Some(&window_box));
+		self.window.present();
+	}
+
 	fn create_window(&self, title: &str, width: i32, height: i32) {
 		if let Some(active_window) = self.app.active_window() {
 			self.window.set_application(Some(self.app));
@@ -146,6 +215,21 @@ impl<'a> Dialogue<'a> {
 		ok_button
 	}
 
+	fn choice_button(&self, label: &str, choice: Box<dyn Fn()>) -> gtk::Button {
+		let choice_button = gtk::Button::builder()
+			.label(label)
+			.width_request(80)
+			.css_classes(["button"])
+			.build();
+
+		let window_clone = self.window.clone();
+		choice_button.connect_clicked(move |_button| {
+			choice();
+			window_clone.close();
+		});
+		choice_button
+	}
+
 	fn cancel_button<F>(&self, cancel: F) -> gtk::Button
 	where
 		F: Fn() + 'static
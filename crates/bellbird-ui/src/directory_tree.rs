@@ -1,17 +1,23 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use glib::MainContext;
 use gtk::{gio, prelude::*};
 use gtk::prelude::WidgetExt;
 
 use bellbird_core::config::{
 	Config,
 	ConfigOptions,
-	ConfigSections
+	ConfigSections,
+	Settings,
+	PANEL_WIDTH_RANGE
 };
 
 use bellbird_core::directories::Directories;
+use bellbird_core::notebooks::Notebooks;
+use bellbird_core::tags::Tags;
 use crate::contextmenu::{BbMenuItem, BbMenuSection, ContextMenu};
+use crate::directory_tree_model_item::DirectoryTreeItem;
 use crate::directory_tree_row::DirectoryTreeRow;
 
 #[derive(Debug, Clone)]
@@ -20,14 +26,29 @@ pub struct TreeItem<'a> {
 	pub path: PathBuf
 }
 
+// how deep the sidebar follows nested folders
+const MAX_TREE_DEPTH: usize = 20;
+
 #[derive(Debug, Clone)]
 pub struct DirectoryTree {
 	pub app: adw::Application,
 	pub path: PathBuf,
 	pub model: gio::ListStore,
+	pub tree_model: gtk::TreeListModel,
 	pub list_view: gtk::ListView,
 	pub current_directory: Rc<RefCell<PathBuf>>,
 	pub selected_ctx_path: Rc<RefCell<PathBuf>>,
+	pub tags_list: gtk::ListBox,
+	tags: Rc<RefCell<Vec<(String, usize)>>>,
+	current_tag: Rc<RefCell<Option<String>>>,
+	pub pinned_list: gtk::ListBox,
+	pinned_directories: Rc<RefCell<Vec<PathBuf>>>,
+	// label and list of the pinned folders, hidden while nothing is pinned
+	pinned_box: gtk::Box,
+	panel_box: gtk::Box,
+	pub notebook_dropdown: gtk::DropDown,
+	// set while the dropdown is rebuilt, selecting a row then is not a switch
+	is_refreshing_notebooks: Rc<Cell<bool>>,
 }
 
 impl<'a> DirectoryTree {
@@ -35,20 +56,31 @@ impl<'a> DirectoryTree {
 		app: &'a adw::Application,
 		path: &'a Path
 	) -> Self {
-		let model = gio::ListStore::new::<gtk::Label>();
-		let model_clone = model.clone();
+		let model = gio::ListStore::new::<DirectoryTreeItem>();
+		let tree_model = gtk::TreeListModel::new(
+			model.clone(),
+			false,
+			false,
+			|item| {
+				let item = item.downcast_ref::<DirectoryTreeItem>()?;
+				let children = item.children();
+				if children.is_empty() {
+					return None
+				}
+
+				let child_model = gio::ListStore::new::<DirectoryTreeItem>();
+				children.iter().for_each(|directory| {
+					child_model.append(&DirectoryTreeItem::from_directory(directory));
+				});
+				Some(child_model.upcast())
+			}
+		);
 
 	  let factory = gtk::SignalListItemFactory::new();
 		factory.connect_setup(move |_factory, item| {
 			let item = item.downcast_ref::<gtk::ListItem>().unwrap();
 			let row = DirectoryTreeRow::default();
-			//let expander = gtk::TreeExpander::new();
-			//let expander_icon = gtk::Image::builder()
-			//	.resource("/com/bellbird/notes/icons/arrow-right.svg")
-			//	.pixel_size(12)
-			//	.build();
-			//expander.set_child(Some(&expander_icon));
-			////row.set_expander(&expander);
+			row.setup_drag_and_drop();
 			item.set_child(Some(&row));
 		});
 
@@ -56,24 +88,31 @@ impl<'a> DirectoryTree {
 		factory.connect_bind(move |_factory, item| {
 			let item = item.downcast_ref::<gtk::ListItem>().unwrap();
 			item.set_selectable(false);
-			let label = item.item().and_downcast::<gtk::Label>().unwrap();
+			let list_row = item.item().and_downcast::<gtk::TreeListRow>().unwrap();
+			let tree_item = list_row.item().and_downcast::<DirectoryTreeItem>().unwrap();
 			let child = item.child().and_downcast::<DirectoryTreeRow>().unwrap();
-			let dir_name = &label.label();
-			let path = PathBuf::from(label.widget_name());
-			let depth_from_root = Directories::get_depth_from_root(&path);
-			let has_children = Directories::dir_has_children(&path);
+			let dir_name = &tree_item.name();
+			let path = PathBuf::from(tree_item.path());
 
+			child.bind_list_row(&list_row, &tree_item);
 			child.append_tree_item(
 				&app_clone,
 				&TreeItem { name: dir_name, path },
 				//&dir_name,
 				//&path,
-				depth_from_root,
-				has_children
+				list_row.depth(),
+				list_row.is_expandable()
 			);
 		});
 
-		let selection_model = gtk::MultiSelection::new(Some(model_clone));
+		factory.connect_unbind(move |_factory, item| {
+			let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+			if let Some(child) = item.child().and_downcast::<DirectoryTreeRow>() {
+				child.unbind_list_row();
+			}
+		});
+
+		let selection_model = gtk::MultiSelection::new(Some(tree_model.clone()));
 
 		let list_view = gtk::ListView::builder()
 			.model(&selection_model)
@@ -85,11 +124,84 @@ impl<'a> DirectoryTree {
 			.single_click_activate(true)
 			.build();
 
+		let tags_list = gtk::ListBox::builder()
+			.selection_mode(gtk::SelectionMode::Single)
+			.activate_on_single_click(true)
+			.css_classes(["tags-list"])
+			.build();
+		tags_list.set_placeholder(Some(&gtk::Label::builder()
+			.label("No tags yet")
+			.halign(gtk::Align::Start)
+			.margin_start(12)
+			.css_classes(["details"])
+			.build()
+		));
+		let tags = Rc::new(RefCell::new(vec![]));
+		let current_tag = Rc::new(RefCell::new(None));
+
+		let pinned_list = gtk::ListBox::builder()
+			.selection_mode(gtk::SelectionMode::Single)
+			.activate_on_single_click(true)
+			.css_classes(["pinned-directories-list"])
+			.build();
+		let pinned_directories: Rc<RefCell<Vec<PathBuf>>> = Rc::new(RefCell::new(vec![]));
+
+		let pinned_directories_clone = pinned_directories.clone();
+		let tags_list_clone = tags_list.clone();
+		let current_tag_clone = current_tag.clone();
+		let list_view_clone = list_view.clone();
+		pinned_list.connect_row_activated(move |pinned_list, row| {
+			let Some(path) = pinned_directories_clone.borrow().get(row.index() as usize).cloned() else {
+				return
+			};
+			let path = path.display().to_string();
+			tags_list_clone.unselect_all();
+			*current_tag_clone.borrow_mut() = None;
+			if let Some(selection_model) = list_view_clone.model() {
+				selection_model.unselect_all();
+			}
+
+			let _ = Config::new().set_config_value(
+				ConfigSections::General.as_str(),
+				ConfigOptions::CurrentDirectory,
+				path.clone()
+			);
+
+			pinned_list
+				.activate_action("app.refresh-notes", Some(&path.to_variant()))
+				.expect("The action `refresh-notes` does not exist.");
+		});
+
+		let tags_clone = tags.clone();
+		let current_tag_clone = current_tag.clone();
+		let list_view_clone = list_view.clone();
+		let pinned_list_clone = pinned_list.clone();
+		tags_list.connect_row_activated(move |tags_list, row| {
+			let Some((tag, _)) = tags_clone.borrow().get(row.index() as usize).cloned() else {
+				return
+			};
+			*current_tag_clone.borrow_mut() = Some(tag.clone());
+			pinned_list_clone.unselect_all();
+			if let Some(selection_model) = list_view_clone.model() {
+				selection_model.unselect_all();
+			}
+			tags_list
+				.activate_action("app.filter-tag", Some(&tag.to_variant()))
+				.expect("The action `filter-tag` does not exist.");
+		});
+
+		let tags_list_clone = tags_list.clone();
+		let current_tag_clone = current_tag.clone();
+		let pinned_list_clone = pinned_list.clone();
 		list_view.connect_activate(move |list_view, position| {
+			tags_list_clone.unselect_all();
+			pinned_list_clone.unselect_all();
+			*current_tag_clone.borrow_mut() = None;
+
 			let model = list_view.model().unwrap();
-			let tree_item = model.item(position).and_downcast::<gtk::Label>().unwrap();
-			let _name = tree_item.label();
-			let path = tree_item.widget_name();
+			let list_row = model.item(position).and_downcast::<gtk::TreeListRow>().unwrap();
+			let tree_item = list_row.item().and_downcast::<DirectoryTreeItem>().unwrap();
+			let path = tree_item.path();
 			model.select_item(position, true);
 
 			let _ = Config::new().set_config_value(
@@ -103,21 +215,95 @@ impl<'a> DirectoryTree {
 				.expect("The action `refresh-notes` does not exist.");
 		});
 
+		let is_refreshing_notebooks = Rc::new(Cell::new(false));
+		let notebook_dropdown = gtk::DropDown::builder()
+			.hexpand(true)
+			.tooltip_text("Notebook")
+			.css_classes(["flat"])
+			.build();
+
+		let is_refreshing_notebooks_clone = is_refreshing_notebooks.clone();
+		notebook_dropdown.connect_selected_notify(move |dropdown| {
+			if is_refreshing_notebooks_clone.get() {
+				return
+			}
+			let Some(name) = dropdown
+				.selected_item()
+				.and_downcast::<gtk::StringObject>()
+				.map(|item| item.string().to_string())
+			else {
+				return
+			};
+			if name == Notebooks::active().name {
+				return
+			}
+
+			dropdown
+				.activate_action("app.switch-notebook", Some(&name.to_variant()))
+				.expect("The action `switch-notebook` does not exist.");
+		});
+
 		Self {
 			app: app.clone(),
 			path: path.to_path_buf(),
 			model,
+			tree_model,
 			list_view,
 			current_directory: Rc::new(RefCell::new(path.to_path_buf())),
 			selected_ctx_path: Rc::new(RefCell::new(path.to_path_buf())),
+			tags_list,
+			tags,
+			current_tag,
+			pinned_list,
+			pinned_directories,
+			pinned_box: gtk::Box::new(gtk::Orientation::Vertical, 0),
+			panel_box: Self::panel_box(),
+			notebook_dropdown,
+			is_refreshing_notebooks,
+		}
+	}
+
+	fn panel_box() -> gtk::Box {
+		let settings = Settings::get();
+		gtk::Box::builder()
+			.orientation(gtk::Orientation::Vertical)
+			.vexpand(true)
+			.valign(gtk::Align::Fill)
+			.width_request(*PANEL_WIDTH_RANGE.start())
+			.visible(settings.sidebar_visible)
+			.name("directories-tree")
+			.css_classes(["directories-panel"])
+			.build()
+	}
+
+	pub fn apply_settings(&self, settings: &Settings) {
+		self.panel_box.set_visible(settings.sidebar_visible);
+		// the width is the position of the pane the panel sits in
+		if let Some(paned) = self.panel_box.parent().and_downcast::<gtk::Paned>() {
+			paned.set_position(settings.sidebar_width);
 		}
 	}
 
+	pub fn refresh_notebooks(&self) {
+		let notebooks = Notebooks::list();
+		let names: Vec<&str> = notebooks.iter().map(|notebook| notebook.name.as_str()).collect();
+		let active = Notebooks::active();
+		let position = notebooks.iter().position(|notebook| notebook.name == active.name);
+
+		self.is_refreshing_notebooks.set(true);
+		self.notebook_dropdown.set_model(Some(&gtk::StringList::new(&names)));
+		self.notebook_dropdown.set_selected(position.map(|position| position as u32).unwrap_or(gtk::INVALID_LIST_POSITION));
+		self.is_refreshing_notebooks.set(false);
+	}
+
 	pub fn update_path(&mut self, path: PathBuf) {
 		self.path = path.clone();
 		self.model.remove_all();
 		self.append_to_model(&path);
+		self.restore_expanded_state();
 		self.set_selection();
+		self.refresh_pinned_directories();
+		self.refresh_tags();
 	}
 
 	pub fn refresh(&mut self) {
@@ -125,29 +311,74 @@ impl<'a> DirectoryTree {
 	}
 
 	fn append_to_model(&self, path: &Path) {
-		if let Some(directories) = Directories::list(&path, 1) {
+		if let Some(directories) = Directories::list(&path, MAX_TREE_DEPTH) {
 			directories.iter().for_each(|directory| {
-				let dir_name = directory.name.clone();
-				let path = directory.path.display().to_string();
-
-				//let tree_item = TreeItem::new();
-				//tree_item.set_name(Some(dir_name));
-				//tree_item.set_path(Some(path));
-				//self.model.append(&tree_item);
+				self.model.append(&DirectoryTreeItem::from_directory(directory));
+			})
+		}
+	}
 
-				//let row = DirectoryTreeRow::new();
-				//row.set_name(Some(dir_name));
-				//row.set_path(Some(directory.path.display().to_string()));
+	// expanding a row inserts its children right after it,
+	// so nested folders are visited by the same loop
+	fn restore_expanded_state(&self) {
+		let mut position = 0;
+		while position < self.tree_model.n_items() {
+			if let Some(list_row) = self.tree_model.row(position) {
+				let is_expanded = list_row
+					.item()
+					.and_downcast::<DirectoryTreeItem>()
+					.map(|item| item.is_expanded())
+					.unwrap_or(false);
+
+				if is_expanded && list_row.is_expandable() {
+					list_row.set_expanded(true);
+				}
+			}
+			position += 1;
+		}
+	}
 
-				let label = gtk::Label::builder()
-					.label(&dir_name)
-					.name(&path)
-					.build();
+	fn refresh_pinned_directories(&self) {
+		let pinned_directories = Directories::pinned_directories();
+		self.pinned_list.remove_all();
+		for path in pinned_directories.iter() {
+			self.pinned_list.append(&pinned_directory_row(path));
+		}
+		self.pinned_box.set_visible(!pinned_directories.is_empty());
 
-				self.model.append(&label);
-				//self.append_to_model(&directory.path);
-			})
+		// the pinned folder is selected instead of the same folder in the tree
+		let current_directory = self.current_directory.borrow().clone();
+		let position = pinned_directories.iter().position(|path| *path == current_directory);
+		if let Some(row) = position.and_then(|position| self.pinned_list.row_at_index(position as i32)) {
+			self.pinned_list.select_row(Some(&row));
 		}
+		*self.pinned_directories.borrow_mut() = pinned_directories;
+	}
+
+	// the rows are only rebuilt when the tags changed, so saving a note
+	// does not reset the selection
+	pub fn refresh_tags(&self) {
+		let self_clone = self.clone();
+		MainContext::default().spawn_local(async move {
+			// the first lookup builds the tag index, keep it off the main thread
+			let tags = gio::spawn_blocking(Tags::all).await.unwrap_or_default();
+			if *self_clone.tags.borrow() == tags {
+				return
+			}
+
+			self_clone.tags_list.remove_all();
+			for (tag, count) in tags.iter() {
+				self_clone.tags_list.append(&tag_row(tag, *count));
+			}
+
+			let current_tag = self_clone.current_tag.borrow().clone();
+			let position = tags.iter().position(|(tag, _)| Some(tag) == current_tag.as_ref());
+			match position.and_then(|position| self_clone.tags_list.row_at_index(position as i32)) {
+				Some(row) => self_clone.tags_list.select_row(Some(&row)),
+				None => self_clone.tags_list.unselect_all(),
+			}
+			*self_clone.tags.borrow_mut() = tags;
+		});
 	}
 
 	fn view(&self) -> &gtk::ListView {
@@ -162,20 +393,22 @@ impl<'a> DirectoryTree {
 		let current_directory = self.current_directory.clone();
 		if let Some(selection_model) = self.list_view.model() {
 			for index in 0..selection_model.n_items() {
-				if let Some(item) = selection_model.item(index) {
-					let path = item.downcast::<gtk::Label>()
-						.unwrap()
-						.widget_name();
-
-					let current_directory = current_directory
-						.borrow_mut()
-						.display()
-						.to_string();
-
-					if path.to_string() == current_directory {
-						selection_model.select_item(index, true);
-						break;
-					}
+				let Some(tree_item) = selection_model
+					.item(index)
+					.and_downcast::<gtk::TreeListRow>()
+					.and_then(|list_row| list_row.item())
+					.and_downcast::<DirectoryTreeItem>() else {
+					continue;
+				};
+
+				let current_directory = current_directory
+					.borrow_mut()
+					.display()
+					.to_string();
+
+				if tree_item.path() == current_directory {
+					selection_model.select_item(index, true);
+					break;
 				}
 			}
 		}
@@ -197,7 +430,7 @@ impl<'a> DirectoryTree {
 		sections.push(BbMenuSection { label: None, items: sec1 });
 
 		let mut sec2 = vec![];
-		//sec2.push(BbMenuItem { label: "Duplicate Folder", action: "duplicate-folder" });
+		sec2.push(BbMenuItem { label: "Duplicate Folder", action: "duplicate-folder" });
 		sec2.push(BbMenuItem { label: "Pin / Unpin Folder", action: "toggle-pin-folder" });
 		sec2.push(BbMenuItem { label: "Rename Folder", action: "rename-folder" });
 		sections.push(BbMenuSection { label: None, items: sec2 });
@@ -259,14 +492,7 @@ pub fn build_ui(
 	app: &adw::Application,
 	directory_tree: &Rc<RefCell<DirectoryTree>>
 ) -> gtk::Box {
-	let directory_panel = gtk::Box::builder()
-		.orientation(gtk::Orientation::Vertical)
-		.vexpand(true)
-		.valign(gtk::Align::Fill)
-		.width_request(190)
-		.name("directories-tree")
-		.css_classes(["directories-panel"])
-		.build();
+	let directory_panel = directory_tree.borrow().panel_box.clone();
 
 	let directory_panel_label = gtk::Label::builder()
 		.label("Folders")
@@ -282,6 +508,41 @@ pub fn build_ui(
 		.hscrollbar_policy(gtk::PolicyType::External)
 		.build();
 
+	let pinned_label = gtk::Label::builder()
+		.label("Pinned")
+		.margin_start(12)
+		.margin_end(10)
+		.margin_top(5)
+		.margin_bottom(5)
+		.halign(gtk::Align::Start)
+		.build();
+
+	let pinned_box = directory_tree.borrow().pinned_box.clone();
+	pinned_box.set_css_classes(&["pinned-directories"]);
+	pinned_box.append(&pinned_label);
+	pinned_box.append(&directory_tree.borrow().pinned_list);
+
+	let notebook_header = gtk::Box::builder()
+		.orientation(gtk::Orientation::Horizontal)
+		.spacing(4)
+		.margin_start(8)
+		.margin_end(8)
+		.margin_top(5)
+		.margin_bottom(5)
+		.css_classes(["notebook-switcher"])
+		.build();
+
+	let new_notebook_button = gtk::Button::builder()
+		.icon_name("list-add-symbolic")
+		.action_name("app.new-notebook")
+		.tooltip_text("New notebook")
+		.css_classes(["flat"])
+		.build();
+
+	directory_tree.borrow().refresh_notebooks();
+	notebook_header.append(&directory_tree.borrow().notebook_dropdown);
+	notebook_header.append(&new_notebook_button);
+
 	directory_tree.borrow_mut().build_context_menu(app);
 
 	//let handle_box = gtk::Box::new(gtk::Orientation::Horizontal, 0);
@@ -292,8 +553,97 @@ pub fn build_ui(
 
 	//handle_box.append(&directory_panel_label);
 	//directory_panel.append(&_window_handle);
+	let tags_label = gtk::Label::builder()
+		.label("Tags")
+		.margin_start(12)
+		.margin_end(10)
+		.margin_top(5)
+		.margin_bottom(5)
+		.halign(gtk::Align::Start)
+		.build();
+
+	let tags_scrollable_window = gtk::ScrolledWindow::builder()
+		.child(&directory_tree.borrow().tags_list)
+		.hscrollbar_policy(gtk::PolicyType::Never)
+		.max_content_height(200)
+		.propagate_natural_height(true)
+		.margin_bottom(5)
+		.build();
+
+	let trash_button = gtk::Button::builder()
+		.action_name("app.show-trash")
+		.halign(gtk::Align::Start)
+		.margin_start(8)
+		.margin_bottom(5)
+		.css_classes(["flat", "trash-button"])
+		.build();
+
+	let trash_button_content = gtk::Box::builder()
+		.orientation(gtk::Orientation::Horizontal)
+		.spacing(6)
+		.build();
+	trash_button_content.append(&gtk::Image::from_icon_name("user-trash-symbolic"));
+	trash_button_content.append(&gtk::Label::new(Some("Trash")));
+	trash_button.set_child(Some(&trash_button_content));
+
+	directory_panel.append(&notebook_header);
+	directory_panel.append(&pinned_box);
 	directory_panel.append(&directory_panel_label);
 	directory_panel.append(&scrollable_window);
+	directory_panel.append(&tags_label);
+	directory_panel.append(&tags_scrollable_window);
+	directory_panel.append(&trash_button);
 
 	directory_panel
 }
+
+fn pinned_directory_row(path: &Path) -> gtk::Box {
+	let row = gtk::Box::builder()
+		.orientation(gtk::Orientation::Horizontal)
+		.spacing(6)
+		.margin_start(12)
+		.margin_end(10)
+		.margin_top(3)
+		.margin_bottom(3)
+		.tooltip_text(path.display().to_string())
+		.build();
+
+	let icon = gtk::Image::from_resource("/com/bellbird/notes/icons/folder-closed.svg");
+	let name = gtk::Label::builder()
+		.label(path.file_name().unwrap_or_default().to_string_lossy().to_string())
+		.halign(gtk::Align::Start)
+		.ellipsize(gtk::pango::EllipsizeMode::End)
+		.build();
+
+	row.append(&icon);
+	row.append(&name);
+	row
+}
+
+fn tag_row(tag: &str, count: usize) -> gtk::Box {
+	let row = gtk::Box::builder()
+		.orientation(gtk::Orientation::Horizontal)
+		.spacing(6)
+		.margin_start(12)
+		.margin_end(10)
+		.margin_top(3)
+		.margin_bottom(3)
+		.build();
+
+	let name = gtk::Label::builder()
+		.label(format!("#{tag}"))
+		.halign(gtk::Align::Start)
+		.hexpand(true)
+		.ellipsize(gtk::pango::EllipsizeMode::End)
+		.build();
+
+	let count = gtk::Label::builder()
+		.label(count.to_string())
+		.halign(gtk::Align::End)
+		.css_classes(["details"])
+		.build();
+
+	row.append(&name);
+	row.append(&count);
+	row
+}
@@ -0,0 +1,135 @@
+use gtk::{
+	glib,
+	prelude::*,
+	subclass::prelude::*,
+};
+use std::cell::RefCell;
+use once_cell::sync::Lazy;
+
+use bellbird_core::directories::Directory;
+
+mod imp {
+	use glib::property::PropertySet;
+
+	use super::*;
+
+	#[derive(Debug, Default)]
+	pub(crate) struct DirectoryTreeItem {
+		pub name: RefCell<String>,
+		pub path: RefCell<String>,
+		pub expanded: RefCell<bool>,
+		pub children: RefCell<Vec<Directory>>,
+	}
+
+	#[glib::object_subclass]
+	impl ObjectSubclass for DirectoryTreeItem {
+		const NAME: &'static str = "DirectoryTreeItem";
+		type Type = super::DirectoryTreeItem;
+		type ParentType = gtk::Widget;
+	}
+
+	impl ObjectImpl for DirectoryTreeItem {
+		fn properties() -> &'static [glib::ParamSpec] {
+			static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+				vec![
+					glib::ParamSpecString::builder("name").default_value(None).build(),
+					glib::ParamSpecString::builder("path").default_value(None).build(),
+					glib::ParamSpecBoolean::builder("expanded").default_value(false).build(),
+				]
+			});
+			PROPERTIES.as_ref()
+		}
+
+		fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+			match pspec.name() {
+				"name" => self.name.borrow().to_value(),
+				"path" => self.path.borrow().to_value(),
+				"expanded" => self.expanded.borrow().to_value(),
+				_ => unimplemented!(),
+			}
+		}
+
+		fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+			match pspec.name() {
+				"name" => {
+					let name = value
+						.get::<String>()
+						.expect("The value needs to be of type `String`");
+					self.name.set(name);
+				},
+				"path" => {
+					let path = value
+						.get::<String>()
+						.expect("The value needs to be of type `String`");
+					self.path.set(path);
+				},
+				"expanded" => {
+					let expanded = value
+						.get::<bool>()
+						.expect("The value needs to be of type `bool`");
+					self.expanded.set(expanded);
+				}
+				_ => unimplemented!(),
+			}
+		}
+	}
+	impl WidgetImpl for DirectoryTreeItem {}
+}
+
+glib::wrapper! {
+	pub(crate) struct DirectoryTreeItem(ObjectSubclass<imp::DirectoryTreeItem>)
+		@extends gtk::Widget;
+}
+
+impl Default for DirectoryTreeItem {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl DirectoryTreeItem {
+	pub(crate) fn new() -> Self {
+		glib::Object::new()
+	}
+
+	pub(crate) fn from_directory(directory: &Directory) -> Self {
+		let item = Self::new();
+		item.set_name(&directory.name);
+		item.set_path(&directory.path.display().to_string());
+		item.set_is_expanded(directory.is_expanded);
+		item.set_children(directory.children.clone());
+		item
+	}
+
+	pub(crate) fn name(&self) -> String {
+		self.property::<String>("name")
+	}
+
+	pub(crate) fn set_name(&self, name: &str) {
+		self.set_property("name", name.to_value());
+	}
+
+	pub(crate) fn path(&self) -> String {
+		self.property::<String>("path")
+	}
+
+	pub(crate) fn set_path(&self, path: &str) {
+		self.set_property("path", path.to_value());
+	}
+
+	pub(crate) fn is_expanded(&self) -> bool {
+		self.property::<bool>("expanded")
+	}
+
+	pub(crate) fn set_is_expanded(&self, expanded: bool) {
+		self.set_property("expanded", expanded.to_value());
+	}
+
+	pub(crate) fn children(&self) -> Vec<Directory> {
+		self.imp().children.borrow().clone()
+	}
+
+	pub(crate) fn set_children(&self, children: Vec<Directory>) {
+		*self.imp().children.borrow_mut() = children;
+	}
+}
@@ -1,4 +1,4 @@
-//use std::cell::RefCell;
+use std::cell::RefCell;
 //use std::path::PathBuf;
 //use std::rc::Rc;
 
@@ -22,6 +22,10 @@ pub struct DirectoryTreeRow {
 	pub name: TemplateChild<gtk::Label>,
 	#[template_child]
 	pub path: TemplateChild<gtk::Label>,
+
+	// the tree row currently bound to the expander and the handler
+	// that persists its expanded state
+	pub expanded_handler: RefCell<Option<(gtk::TreeListRow, glib::SignalHandlerId)>>,
 }
 
 impl Default for DirectoryTreeRow {
@@ -33,6 +37,7 @@ impl Default for DirectoryTreeRow {
 			icon: TemplateChild::<gtk::Image>::default(),
 			name: TemplateChild::<gtk::Label>::default(),
 			path: TemplateChild::<gtk::Label>::default(),
+			expanded_handler: RefCell::new(None),
 		}
 	}
 }
@@ -1,11 +1,13 @@
 pub(crate) mod imp;
 
-//use std::path::Path;
+use std::path::PathBuf;
 
-use gtk::{glib, pango::EllipsizeMode, subclass::prelude::*};
+use bellbird_core::directories::Directories;
+use gtk::{gdk, glib, pango::EllipsizeMode, prelude::*, subclass::prelude::*};
 //use adw::prelude::*;
 
 use crate::directory_tree::TreeItem;
+use crate::directory_tree_model_item::DirectoryTreeItem;
 
 glib::wrapper! {
 	pub struct DirectoryTreeRow(ObjectSubclass<imp::DirectoryTreeRow>)
@@ -74,4 +76,63 @@ impl DirectoryTreeRow {
 		imp.name.set_ellipsize(EllipsizeMode::End);
 		imp.path.set_text(&path.display().to_string());
 	}
+
+	pub(crate) fn path(&self) -> PathBuf {
+		PathBuf::from(self.imp().path.text().as_str())
+	}
+
+	// folders can be dragged onto other folders, notes and
+	// folders dropped onto a folder are moved into it
+	pub(crate) fn setup_drag_and_drop(&self) {
+		let drag_source = gtk::DragSource::builder()
+			.actions(gdk::DragAction::MOVE)
+			.build();
+
+		drag_source.connect_prepare(|drag_source, _x, _y| {
+			let row = drag_source.widget().and_downcast::<DirectoryTreeRow>()?;
+			let path = row.path().display().to_string();
+			Some(gdk::ContentProvider::for_value(&path.to_value()))
+		});
+		self.add_controller(drag_source);
+
+		let drop_target = gtk::DropTarget::new(String::static_type(), gdk::DragAction::MOVE);
+		drop_target.connect_drop(|drop_target, value, _x, _y| {
+			let Some(row) = drop_target.widget().and_downcast::<DirectoryTreeRow>() else {
+				return false
+			};
+			let Ok(source) = value.get::<String>() else {
+				return false
+			};
+
+			let target = row.path().display().to_string();
+			if source == target {
+				return false
+			}
+			row.activate_action("app.move-to", Some(&(source, target).to_variant())).is_ok()
+		});
+		self.add_controller(drop_target);
+	}
+
+	pub(crate) fn bind_list_row(&self, list_row: &gtk::TreeListRow, item: &DirectoryTreeItem) {
+		let imp = self.imp();
+		imp.expander.set_list_row(Some(list_row));
+
+		let handler_id = list_row.connect_expanded_notify(glib::clone!(
+			#[weak] item,
+			move |list_row| {
+				let is_expanded = list_row.is_expanded();
+				item.set_is_expanded(is_expanded);
+				Directories::set_is_expanded(&PathBuf::from(item.path()), is_expanded);
+			}
+		));
+		imp.expanded_handler.replace(Some((list_row.clone(), handler_id)));
+	}
+
+	pub(crate) fn unbind_list_row(&self) {
+		let imp = self.imp();
+		if let Some((list_row, handler_id)) = imp.expanded_handler.take() {
+			list_row.disconnect(handler_id);
+		}
+		imp.expander.set_list_row(None::<&gtk::TreeListRow>);
+	}
 }
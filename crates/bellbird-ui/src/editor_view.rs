@@ -4,8 +4,11 @@ use std::cell::{Cell, RefCell};
 //use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use bellbird_core::config::{Config, ConfigOptions};
+use bellbird_core::config::{Config, ConfigOptions, ConfigSections, Settings};
+use bellbird_core::history::History;
+use bellbird_core::links::Links;
 use bellbird_core::notes::Notes;
+use bellbird_core::search::Query;
 use gtk::{gio, glib, prelude::*};
 use sourceview5::{
 	self,
@@ -14,14 +17,69 @@ use sourceview5::{
 	prelude::ViewExt
 };
 
-use crate::breadcrumb::Breadcrumb;
+use crate::backlinks_panel::BacklinksPanel;
+use crate::breadcrumb::{Breadcrumb, SaveState};
+use crate::history_panel::HistoryPanel;
+use crate::markdown_preview::MarkdownPreview;
+
+const SEARCH_MATCH_TAG: &str = "search-match";
+const LANGUAGE_SPECS_PATH: &str = "resource:///com/bellbird/notes/language-specs/";
+const STYLES_PATH: &str = "resource:///com/bellbird/notes/styles/";
+const MARKDOWN_LANGUAGE: &str = "bellbird-markdown";
+const STYLE_SCHEME: &str = "bellbird";
+// how long typing has to pause before the note gets written
+const SAVE_DELAY: Duration = Duration::from_millis(750);
+
+// a note open in one of the editor's tabs. Every tab keeps its own buffer,
+// so undo history, caret position and unsaved changes survive switching
+#[derive(Debug, Clone)]
+pub struct EditorTab {
+	pub path: PathBuf,
+	pub buffer: Buffer,
+	page: adw::TabPage,
+	save_state: Rc<RefCell<SaveState>>,
+	save_timeout: Rc<Cell<Option<glib::SourceId>>>,
+	// the note as it was last loaded or saved, the base of a merge
+	saved_content: Rc<RefCell<String>>,
+}
+
+impl EditorTab {
+	fn set_save_state(&self, state: SaveState, breadcrumb: &Breadcrumb) {
+		let name = self.path.file_stem()
+			.map(|stem| stem.to_string_lossy().to_string())
+			.unwrap_or_default();
+		let title = match state {
+			SaveState::Saved => name,
+			_ => format!("• {name}"),
+		};
+		self.page.set_title(&title);
+		self.page.set_needs_attention(matches!(state, SaveState::Failed(_)));
+
+		if self.page.is_selected() {
+			breadcrumb.set_save_state(&state);
+		}
+		*self.save_state.borrow_mut() = state;
+	}
+
+	fn cancel_save(&self) {
+		if let Some(timeout_id) = self.save_timeout.take() {
+			timeout_id.remove();
+		}
+	}
+}
 
 #[derive(Debug, Clone)]
 pub struct Editor {
+	// the note of the selected tab
 	pub path: PathBuf,
-	pub buffer: Buffer,
 	pub editor_view: View,
 	pub editor_breadcrumb: Breadcrumb,
+	pub tab_view: adw::TabView,
+	tabs: Rc<RefCell<Vec<EditorTab>>>,
+	// search query whose matches get highlighted once the next note has loaded
+	pub highlight_query: Rc<RefCell<Option<String>>>,
+	// the editor font, see `apply_settings`
+	font_css: gtk::CssProvider,
 	//file_finished_loading: Arc<Mutex<f32>>,
 }
 
@@ -59,26 +117,77 @@ impl<'a> Editor {
 		});
 		editor_view.add_controller(controller.clone());
 
+		// ctrl+click on a `[[wiki link]]` opens the linked note
+		let link_click = gtk::GestureClick::builder()
+			.propagation_phase(gtk::PropagationPhase::Capture)
+			.build();
+		link_click.connect_pressed(|gesture, _n_press, x, y| {
+			if !gesture.current_event_state().contains(gtk::gdk::ModifierType::CONTROL_MASK) {
+				return
+			}
+			let Some(view) = gesture.widget().and_downcast::<View>() else {
+				return
+			};
+			if let Some(target) = link_at_location(&view, x, y) {
+				gesture.set_state(gtk::EventSequenceState::Claimed);
+				open_link(&view, &target);
+			}
+		});
+		editor_view.add_controller(link_click);
+
+		let tab_view = adw::TabView::builder()
+			.hexpand(true)
+			.vexpand(true)
+			.build();
+
+		let font_css = gtk::CssProvider::new();
+		if let Some(display) = gtk::gdk::Display::default() {
+			gtk::style_context_add_provider_for_display(
+				&display,
+				&font_css,
+				gtk::STYLE_PROVIDER_PRIORITY_APPLICATION + 1,
+			);
+		}
+
 		//let file_finished_loading = Arc::new(Mutex::new(0.0));
 
-		Self {
+		let editor = Self {
 			path: path.to_path_buf(),
-			buffer,
 			editor_view,
 			editor_breadcrumb,
+			tab_view,
+			tabs: Rc::new(RefCell::new(vec![])),
+			highlight_query: Rc::new(RefCell::new(None)),
+			font_css,
 			//file_finished_loading,
-		}
+		};
+		editor.apply_settings(&Settings::get());
+		editor
+	}
+
+	pub fn apply_settings(&self, settings: &Settings) {
+		self.font_css.load_from_string(&format!(
+			".editor-panel textview {{ font-size: {}px; }}",
+			settings.font_size
+		));
+		self.editor_breadcrumb.set_visible(settings.breadcrumb_visible);
 	}
 
-	pub fn add_buffer(&self, path: &PathBuf) -> sourceview5::Buffer {
+	pub fn add_buffer(&self, path: &PathBuf, saved_content: &Rc<RefCell<String>>) -> sourceview5::Buffer {
 		let buffer = sourceview5::Buffer::new(None);
-		//buffer.set_highlight_syntax(true);
+		buffer.set_language(markdown_language().as_ref());
+		buffer.set_style_scheme(style_scheme().as_ref());
+		buffer.set_highlight_syntax(true);
 		let file = gio::File::for_path(path);
 		let file = sourceview5::File::builder().location(&file).build();
 		let loader = sourceview5::FileLoader::new(&buffer, &file);
 		//let path_clone = path.display().to_string();
 
 		//let file_finished_loading = Arc::clone(&self.file_finished_loading);
+		let highlight_query = self.highlight_query.clone();
+		let editor_view = self.editor_view.clone();
+		let buffer_clone = buffer.clone();
+		let saved_content = saved_content.clone();
 
 		//loader.load_async(glib::Priority::default(), gio::Cancellable::NONE, move |_| {});
 		loader.load_async_with_callback(
@@ -95,6 +204,13 @@ impl<'a> Editor {
 				//*finished = percentage;
 			},
 			move |_res| {
+				buffer_clone.set_modified(false);
+				*saved_content.borrow_mut() = buffer_clone
+					.text(&buffer_clone.start_iter(), &buffer_clone.end_iter(), false)
+					.to_string();
+				if let Some(query) = highlight_query.take() {
+					highlight_matches(&editor_view, &buffer_clone, &query);
+				}
 
 				//println!("loaded {:?}", res);
 				//let mut finished = file_finished_loading.lock().unwrap();
@@ -108,30 +224,205 @@ impl<'a> Editor {
 		buffer
 	}
 
-	pub async fn update_path(&mut self, path: PathBuf) {
-		self.path = path.clone();
-		let buffer = self.add_buffer(&path);
-		// disable editor if no note is loaded to avoid
-		// writing into nothing
-		//if self.path.exists() {
-		//	self.set_editor_editable(true);
-		//}
-		//else {
-		//	self.set_editor_editable(false);
-		//}
+	// opens the note in the selected tab, or switches to the tab
+	// that already shows it. If the selected tab's note can't be saved
+	// the tab is kept and the note opens in a new tab, the error is returned
+	pub async fn update_path(&mut self, path: PathBuf) -> anyhow::Result<()> {
+		if let Some(tab) = self.tab(&path) {
+			self.select_tab(&tab).await;
+			self.highlight_search_matches(&tab);
+			return Ok(())
+		}
+
+		let mut result = Ok(());
+		let tab = match self.current_tab() {
+			Some(current_tab) => match self.flush() {
+				Ok(()) => self.replace_tab(&current_tab, &path),
+				Err(e) => {
+					result = Err(e);
+					self.add_tab(&path)
+				}
+			},
+			None => self.add_tab(&path)
+		};
+		self.select_tab(&tab).await;
+		self.place_cursor(&tab.buffer).await;
+		self.store_open_notes();
+		result
+	}
+
+	pub async fn open_in_new_tab(&mut self, path: PathBuf) {
+		if let Some(tab) = self.tab(&path) {
+			self.select_tab(&tab).await;
+			self.highlight_search_matches(&tab);
+			return
+		}
+
+		let tab = self.add_tab(&path);
+		self.select_tab(&tab).await;
+		self.place_cursor(&tab.buffer).await;
+		self.store_open_notes();
+	}
+
+	// brings back the tabs of the last session, notes that are gone by now
+	// are skipped
+	pub async fn restore_tabs(&mut self, paths: Vec<PathBuf>, current_path: PathBuf) {
+		for path in paths.iter().filter(|path| path.is_file()) {
+			if self.tab(path).is_none() {
+				self.add_tab(path);
+			}
+		}
+
+		if current_path.is_file() {
+			self.open_in_new_tab(current_path).await;
+			return
+		}
+		let first_tab = self.tabs.borrow().first().cloned();
+		if let Some(tab) = first_tab {
+			self.select_tab(&tab).await;
+		}
+	}
+
+	// saves the tab's note and forgets about the tab, the page itself
+	// gets closed by the tab view
+	pub fn close_tab(&mut self, page: &adw::TabPage) -> anyhow::Result<()> {
+		let Some(tab) = self.tab_for_page(page) else {
+			return Ok(())
+		};
+		self.flush_tab(&tab)?;
+
+		self.tabs.borrow_mut().retain(|tab| &tab.page != page);
+		self.store_open_notes();
+		if self.tabs.borrow().is_empty() {
+			self.clear();
+		}
+		Ok(())
+	}
+
+	pub fn tab_for_page(&self, page: &adw::TabPage) -> Option<EditorTab> {
+		self.tabs.borrow().iter().find(|tab| &tab.page == page).cloned()
+	}
+
+	fn tab(&self, path: &Path) -> Option<EditorTab> {
+		self.tabs.borrow().iter().find(|tab| tab.path == path).cloned()
+	}
+
+	fn current_tab(&self) -> Option<EditorTab> {
+		self.tab_view.selected_page().and_then(|page| self.tab_for_page(&page))
+	}
+
+	fn add_tab(&self, path: &Path) -> EditorTab {
+		let page = self.tab_view.append(&gtk::Box::new(gtk::Orientation::Vertical, 0));
+		let tab = self.load_tab(path, &page);
+		self.tabs.borrow_mut().push(tab.clone());
+		tab
+	}
+
+	// shows another note in an existing tab
+	fn replace_tab(&self, tab: &EditorTab, path: &Path) -> EditorTab {
+		tab.cancel_save();
+		let new_tab = self.load_tab(path, &tab.page);
+		for existing_tab in self.tabs.borrow_mut().iter_mut() {
+			if existing_tab.page == tab.page {
+				*existing_tab = new_tab.clone();
+			}
+		}
+		new_tab
+	}
+
+	fn load_tab(&self, path: &Path, page: &adw::TabPage) -> EditorTab {
+		let saved_content = Rc::new(RefCell::new(String::new()));
+		let buffer = self.add_buffer(&path.to_path_buf(), &saved_content);
+		let tab = EditorTab {
+			path: path.to_path_buf(),
+			buffer: buffer.clone(),
+			page: page.clone(),
+			save_state: Rc::new(RefCell::new(SaveState::Saved)),
+			save_timeout: Rc::new(Cell::new(None)),
+			saved_content,
+		};
+		page.set_tooltip(&path.display().to_string());
+		tab.set_save_state(SaveState::Saved, &self.editor_breadcrumb);
+		let _ = self.write_caret_position_to_file(&tab.path, buffer);
+		tab
+	}
+
+	async fn select_tab(&mut self, tab: &EditorTab) {
+		self.path = tab.path.clone();
+		self.editor_view.set_buffer(Some(&tab.buffer));
+		if !tab.page.is_selected() {
+			self.tab_view.set_selected_page(&tab.page);
+		}
+		self.set_editor_editable(true);
 
-		self.editor_view.set_buffer(Some(&buffer));
 		self.editor_breadcrumb = self.build_breadcrumb().await.clone();
+		let save_state = tab.save_state.borrow().clone();
+		self.editor_breadcrumb.set_save_state(&save_state);
 		self.editor_view.queue_draw();
-		self.place_cursor(&buffer).await;
+		self.editor_view.scroll_mark_onscreen(&tab.buffer.get_insert());
 		self.editor_view.grab_focus();
+	}
+
+	// a note that is open already doesn't load again, so the pending
+	// search query is applied right away instead of being left for the next one
+	fn highlight_search_matches(&self, tab: &EditorTab) {
+		if let Some(query) = self.highlight_query.take() {
+			highlight_matches(&self.editor_view, &tab.buffer, &query);
+		}
+	}
+
+	// nothing is open after the last tab was closed
+	fn clear(&mut self) {
+		self.path = PathBuf::new();
+		self.editor_view.set_buffer(Some(&Buffer::new(None)));
+		self.set_editor_editable(false);
+		self.editor_breadcrumb.set_save_state(&SaveState::Saved);
+		Notes::set_current_path(&self.path);
+
+		let breadcrumb = self.editor_breadcrumb.clone();
+		glib::MainContext::default().spawn_local(async move {
+			breadcrumb.build(Path::new("")).await;
+		});
+	}
+
+	fn store_open_notes(&self) {
+		let tabs = self.tabs.borrow();
+		let paths: Vec<PathBuf> = (0..self.tab_view.n_pages())
+			.map(|position| self.tab_view.nth_page(position))
+			.filter_map(|page| tabs.iter().find(|tab| tab.page == page))
+			.map(|tab| tab.path.clone())
+			.collect();
+		Notes::set_open_paths(&paths);
+	}
+
+	// notes open in tabs were moved, on their own or along with their folder
+	pub async fn paths_moved(&mut self, old_path: &Path, new_path: &Path) {
+		for tab in self.tabs.borrow_mut().iter_mut() {
+			let Some(path) = moved_path(&tab.path, old_path, new_path) else {
+				continue
+			};
+			tab.path = path;
+			tab.page.set_tooltip(&tab.path.display().to_string());
+			let save_state = tab.save_state.borrow().clone();
+			tab.set_save_state(save_state, &self.editor_breadcrumb);
+		}
+		self.store_open_notes();
+
+		if let Some(path) = moved_path(&self.path, old_path, new_path) {
+			self.path = path;
+			Notes::set_current_path(&self.path);
+			self.build_breadcrumb().await;
+		}
+	}
 
-		let _ = self.write_caret_position_to_file(buffer);
+	// the tab order changed
+	pub fn tabs_reordered(&self) {
+		self.store_open_notes();
 	}
 
 	fn caret_position(&self) -> Option<i32> {
 		match Config::new().meta_info(
-			&self.path.display().to_string(),
+			&Notes::meta_info_section(&self.path),
 			ConfigOptions::CaretPosition
 		) {
 			Some(position) => position.parse().ok(),
@@ -157,13 +448,13 @@ impl<'a> Editor {
 		}
 	}
 
-	fn write_caret_position_to_file(&self, buffer: Buffer) -> anyhow::Result<()> {
+	fn write_caret_position_to_file(&self, path: &Path, buffer: Buffer) -> anyhow::Result<()> {
 		let typing_timeout: Rc<Cell<Option<glib::SourceId>>> = Rc::new(Cell::new(None));
 		let config = Config::new();
 
 		buffer.connect_cursor_position_notify({
 			let typing_timeout = typing_timeout.clone();
-			let path = self.path.clone();
+			let path = path.to_path_buf();
 			move |buffer| {
 				if let Some(timeout_id) = typing_timeout.take() {
 					timeout_id.remove();
@@ -177,7 +468,7 @@ impl<'a> Editor {
 					move || {
 						let buffer = buffer.clone();
 						let _ = config.set_meta_value(
-							&path.display().to_string(),
+							&Notes::meta_info_section(&path),
 							ConfigOptions::CaretPosition,
 							buffer.cursor_position().to_string()
 						);
@@ -214,14 +505,272 @@ impl<'a> Editor {
 		&self.editor_breadcrumb
 	}
 
-	pub fn write_note(&self) {
-		let buffer_clone = self.editor_view.buffer();
-		let buffer_start = buffer_clone.start_iter();
-		let buffer_end = buffer_clone.end_iter();
-		let _ = Notes::write_to_file(
-			self.path.clone(),
-			buffer_clone.text(&buffer_start, &buffer_end, false).to_string()
-		);
+	// drops unsaved changes and loads the note again from disk
+	pub fn reload(&self) {
+		let Some(tab) = self.current_tab() else {
+			return
+		};
+
+		let tab = self.replace_tab(&tab, &tab.path);
+		self.editor_view.set_buffer(Some(&tab.buffer));
+	}
+
+	// a note in a tab that is not selected changed on disk, tabs
+	// with unsaved changes are left alone
+	pub fn reload_tab(&self, path: &Path) {
+		let Some(tab) = self.tab(path) else {
+			return
+		};
+
+		if !tab.page.is_selected() && !tab.buffer.is_modified() {
+			self.replace_tab(&tab, path);
+		}
+	}
+
+	// the app itself rewrote the notes, e.g. to update their links, so their
+	// tabs load them again. Pending changes have to be flushed before
+	pub fn reload_notes(&self, paths: &[PathBuf]) {
+		for path in paths.iter() {
+			let Some(tab) = self.tab(path) else {
+				continue
+			};
+			let is_selected = tab.page.is_selected();
+			let tab = self.replace_tab(&tab, path);
+			if is_selected {
+				self.editor_view.set_buffer(Some(&tab.buffer));
+			}
+		}
+	}
+
+	pub fn open_paths(&self) -> Vec<PathBuf> {
+		self.tabs.borrow().iter().map(|tab| tab.path.clone()).collect()
+	}
+
+	// combines unsaved changes with what was changed on disk and saves the
+	// result, returns how many conflicts were left for the user to resolve
+	pub fn merge_with_disk(&self) -> anyhow::Result<usize> {
+		let Some(tab) = self.current_tab() else {
+			return Ok(0)
+		};
+		let disk_content = std::fs::read_to_string(&tab.path)?;
+		let buffer = &tab.buffer;
+		let content = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+
+		let base = tab.saved_content.borrow().clone();
+		let merge = History::merge(&base, &content, &disk_content);
+		buffer.set_text(&merge.content);
+		self.flush()?;
+		Ok(merge.conflicts)
+	}
+
+	pub fn has_unsaved_changes(&self) -> bool {
+		self.editor_view.buffer().is_modified()
+	}
+
+	// called on every key release, the note is written once typing pauses
+	pub fn schedule_save(&self) {
+		let Some(tab) = self.current_tab() else {
+			return
+		};
+		if !tab.buffer.is_modified() {
+			return
+		}
+
+		tab.set_save_state(SaveState::Unsaved, &self.editor_breadcrumb);
+		tab.cancel_save();
+
+		let timeout_id = glib::timeout_add_local_once(SAVE_DELAY, {
+			let tab = tab.clone();
+			let breadcrumb = self.editor_breadcrumb.clone();
+			move || {
+				tab.save_timeout.set(None);
+				let _ = save_note(&tab, &breadcrumb);
+			}
+		});
+		tab.save_timeout.set(Some(timeout_id));
+	}
+
+	// writes pending changes right away, e.g. before another note is opened
+	pub fn flush(&self) -> anyhow::Result<()> {
+		match self.current_tab() {
+			Some(tab) => self.flush_tab(&tab),
+			None => Ok(())
+		}
+	}
+
+	// writes the pending changes of every tab, e.g. before the window closes
+	pub fn flush_all(&self) -> anyhow::Result<()> {
+		let tabs = self.tabs.borrow().clone();
+		let mut result = Ok(());
+		for tab in tabs.iter() {
+			if let Err(e) = self.flush_tab(tab) {
+				result = Err(e);
+			}
+		}
+		result
+	}
+
+	fn flush_tab(&self, tab: &EditorTab) -> anyhow::Result<()> {
+		tab.cancel_save();
+		if !tab.buffer.is_modified() {
+			return Ok(())
+		}
+		save_note(tab, &self.editor_breadcrumb)
+	}
+
+	pub fn write_note(&self) -> anyhow::Result<()> {
+		match self.current_tab() {
+			Some(tab) => save_note(&tab, &self.editor_breadcrumb),
+			None => Ok(())
+		}
+	}
+}
+
+// where `path` ended up after `old_path` was moved to `new_path`,
+// `None` if it was not affected
+pub fn moved_path(path: &Path, old_path: &Path, new_path: &Path) -> Option<PathBuf> {
+	let relative_path = path.strip_prefix(old_path).ok()?;
+	match relative_path.as_os_str().is_empty() {
+		true => Some(new_path.to_path_buf()),
+		false => Some(new_path.join(relative_path)),
+	}
+}
+
+fn save_note(tab: &EditorTab, breadcrumb: &Breadcrumb) -> anyhow::Result<()> {
+	// nothing to write to as long as no note is open
+	if tab.path.as_os_str().is_empty() {
+		return Ok(())
+	}
+
+	let buffer = &tab.buffer;
+	let content = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false).to_string();
+	match Notes::write_to_file(tab.path.clone(), content.clone()) {
+		Ok(()) => {
+			buffer.set_modified(false);
+			*tab.saved_content.borrow_mut() = content;
+			tab.set_save_state(SaveState::Saved, breadcrumb);
+			// the note might have gained or lost tags
+			let _ = breadcrumb.activate_action("app.refresh-tags", None);
+			Ok(())
+		},
+		Err(e) => {
+			tab.set_save_state(SaveState::Failed(e.to_string()), breadcrumb);
+			Err(e)
+		}
+	}
+}
+
+// the target of the wiki link under the given widget coordinates
+fn link_at_location(view: &View, x: f64, y: f64) -> Option<String> {
+	let (buffer_x, buffer_y) = view.window_to_buffer_coords(
+		gtk::TextWindowType::Widget,
+		x as i32,
+		y as i32
+	);
+	let iter = view.iter_at_location(buffer_x, buffer_y)?;
+
+	// links never span more than one line
+	let mut line_start = iter.clone();
+	line_start.set_line_offset(0);
+	let mut line_end = iter.clone();
+	if !line_end.ends_line() {
+		line_end.forward_to_line_end();
+	}
+
+	let line = view.buffer().text(&line_start, &line_end, false);
+	let offset = iter.line_offset() as usize;
+	Links::parse(&line)
+		.into_iter()
+		.find(|link| link.range.contains(&offset))
+		.map(|link| link.target)
+}
+
+fn open_link(view: &View, target: &str) {
+	match Links::resolve(target, Notes::current_path().as_deref()) {
+		Some(path) => open_note(view, &path),
+		None => eprintln!("There is no note called ´{}´", target),
+	}
+}
+
+// opens a note together with its folder, wherever in the tree it is
+pub fn open_note(widget: &impl IsA<gtk::Widget>, path: &Path) {
+	let directory = path.parent()
+		.map(|parent| parent.display().to_string())
+		.unwrap_or_default();
+	let path = path.display().to_string();
+
+	let mut config = Config::new();
+	let _ = config.set_config_value(
+		ConfigSections::General.as_str(),
+		ConfigOptions::CurrentDirectory,
+		directory.clone()
+	);
+	let _ = config.set_config_value(
+		ConfigSections::General.as_str(),
+		ConfigOptions::CurrentNote,
+		path.clone()
+	);
+
+	widget
+		.activate_action("app.refresh-notes", Some(&directory.to_variant()))
+		.expect("The action `refresh-notes` does not exist.");
+	widget
+		.activate_action("app.open-note", Some(&path.to_variant()))
+		.expect("The action `open-note` does not exist.");
+}
+
+// makes our language definition and style scheme known to sourceview,
+// has to run before the first buffer asks for a language
+pub fn register_syntax_highlighting() {
+	let language_manager = sourceview5::LanguageManager::default();
+	let mut search_path: Vec<String> = language_manager.search_path()
+		.iter()
+		.map(|path| path.to_string())
+		.collect();
+	search_path.insert(0, LANGUAGE_SPECS_PATH.to_string());
+	let search_path: Vec<&str> = search_path.iter().map(|path| path.as_str()).collect();
+	language_manager.set_search_path(&search_path);
+
+	let scheme_manager = sourceview5::StyleSchemeManager::default();
+	scheme_manager.prepend_search_path(STYLES_PATH);
+}
+
+// falls back to the markdown definition shipped with sourceview
+fn markdown_language() -> Option<sourceview5::Language> {
+	let language_manager = sourceview5::LanguageManager::default();
+	language_manager.language(MARKDOWN_LANGUAGE)
+		.or_else(|| language_manager.language("markdown"))
+}
+
+fn style_scheme() -> Option<sourceview5::StyleScheme> {
+	sourceview5::StyleSchemeManager::default().scheme(STYLE_SCHEME)
+}
+
+fn highlight_matches(view: &View, buffer: &Buffer, query: &str) {
+	let Some(query) = Query::parse(query) else {
+		return
+	};
+
+	// matches of an earlier search in the same buffer
+	match buffer.tag_table().lookup(SEARCH_MATCH_TAG) {
+		Some(_) => buffer.remove_tag_by_name(SEARCH_MATCH_TAG, &buffer.start_iter(), &buffer.end_iter()),
+		None => {
+			buffer.create_tag(Some(SEARCH_MATCH_TAG), &[("background", &"#FCEB9C")]);
+		}
+	}
+
+	let content = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+	let ranges = query.match_ranges(&content);
+	for range in ranges.iter() {
+		let start = buffer.iter_at_offset(range.start as i32);
+		let end = buffer.iter_at_offset(range.end as i32);
+		buffer.apply_tag_by_name(SEARCH_MATCH_TAG, &start, &end);
+	}
+
+	if let Some(first_match) = ranges.first() {
+		let mut iter = buffer.iter_at_offset(first_match.start as i32);
+		buffer.place_cursor(&iter);
+		view.scroll_to_iter(&mut iter, 0.1, false, 0.0, 0.0);
 	}
 }
 
@@ -234,15 +783,88 @@ pub fn build_ui(editor: &Rc<RefCell<Editor>>) -> gtk::Box {
 		.margin_end(4)
 		.build();
 
+	let tab_view = editor.borrow().tab_view.clone();
+	let tab_bar = adw::TabBar::builder()
+		.view(&tab_view)
+		.autohide(false)
+		.css_classes(["editor-tabs"])
+		.build();
+
 	let editor_top_bar = gtk::Box::builder()
 		.orientation(gtk::Orientation::Horizontal)
 		.build();
 
 	editor_top_bar.append(editor.borrow_mut().breadcrumb());
 
+	let markdown_preview = MarkdownPreview::new(editor);
+	let preview_button = gtk::ToggleButton::builder()
+		.icon_name("x-office-document-symbolic")
+		.tooltip_text("Show preview")
+		.hexpand(true)
+		.halign(gtk::Align::End)
+		.valign(gtk::Align::Center)
+		.css_classes(["flat", "preview-button"])
+		.build();
+	let markdown_preview_clone = markdown_preview.clone();
+	preview_button.connect_toggled(move |button| {
+		markdown_preview_clone.set_revealed(button.is_active());
+	});
+	editor_top_bar.append(&preview_button);
+
+	let backlinks_panel = BacklinksPanel::new(editor);
+	let backlinks_button = gtk::ToggleButton::builder()
+		.icon_name("mail-forward-symbolic")
+		.tooltip_text("Show backlinks")
+		.halign(gtk::Align::End)
+		.valign(gtk::Align::Center)
+		.css_classes(["flat", "backlinks-button"])
+		.build();
+	let backlinks_panel_clone = backlinks_panel.clone();
+	backlinks_button.connect_toggled(move |button| {
+		backlinks_panel_clone.set_revealed(button.is_active());
+	});
+	editor_top_bar.append(&backlinks_button);
+
+	let history_panel = HistoryPanel::new(editor);
+	let history_button = gtk::ToggleButton::builder()
+		.icon_name("document-open-recent-symbolic")
+		.tooltip_text("Show note history")
+		.halign(gtk::Align::End)
+		.valign(gtk::Align::Center)
+		.css_classes(["flat", "history-button"])
+		.build();
+	let history_panel_clone = history_panel.clone();
+	history_button.connect_toggled(move |button| {
+		history_panel_clone.set_revealed(button.is_active());
+	});
+	editor_top_bar.append(&history_button);
+
+	let preferences_button = gtk::Button::builder()
+		.icon_name("preferences-system-symbolic")
+		.tooltip_text("Preferences")
+		.action_name("app.show-preferences")
+		.halign(gtk::Align::End)
+		.valign(gtk::Align::Center)
+		.margin_end(5)
+		.css_classes(["flat", "preferences-button"])
+		.build();
+	editor_top_bar.append(&preferences_button);
+
 	let scrollable_window = gtk::ScrolledWindow::builder()
 		.child(editor.borrow_mut().view())
+		.hexpand(true)
+		.vexpand(true)
+		.build();
+	connect_tab_signals(editor, &tab_view, &scrollable_window);
+
+	let editor_content = gtk::Box::builder()
+		.orientation(gtk::Orientation::Horizontal)
+		.vexpand(true)
 		.build();
+	editor_content.append(&tab_view);
+	editor_content.append(markdown_preview.widget());
+	editor_content.append(backlinks_panel.widget());
+	editor_content.append(history_panel.widget());
 
 	let handle_box = gtk::Box::new(gtk::Orientation::Horizontal, 0);
 	handle_box.append(&gtk::WindowControls::new(gtk::PackType::End));
@@ -251,8 +873,108 @@ pub fn build_ui(editor: &Rc<RefCell<Editor>>) -> gtk::Box {
 		.build();
 
 	//editor_panel.append(&_window_handle);
+	editor_panel.append(&tab_bar);
 	editor_panel.append(&editor_top_bar);
-	editor_panel.append(&scrollable_window);
+	editor_panel.append(&editor_content);
 
 	editor_panel
 }
+
+pub fn close_all_tabs(editor: &Rc<RefCell<Editor>>) {
+	close_tabs(editor, |_| true);
+}
+
+// closes the tabs of the notes at or below `path`, e.g. once they were trashed
+pub fn close_tabs_below(editor: &Rc<RefCell<Editor>>, path: &Path) {
+	close_tabs(editor, |tab_path| tab_path.starts_with(path));
+}
+
+// closes the tabs the way the close buttons do, so the editor must not be
+// borrowed while the pages go
+fn close_tabs<F>(editor: &Rc<RefCell<Editor>>, should_close: F)
+where
+	F: Fn(&Path) -> bool
+{
+	let (tab_view, pages) = {
+		let editor = editor.borrow();
+		let pages: Vec<adw::TabPage> = editor.tabs
+			.borrow()
+			.iter()
+			.filter(|tab| should_close(&tab.path))
+			.map(|tab| tab.page.clone())
+			.collect();
+		(editor.tab_view.clone(), pages)
+	};
+	for page in pages.iter() {
+		tab_view.close_page(page);
+	}
+}
+
+fn connect_tab_signals(
+	editor: &Rc<RefCell<Editor>>,
+	tab_view: &adw::TabView,
+	scrollable_window: &gtk::ScrolledWindow,
+) {
+	tab_view.connect_selected_page_notify(glib::clone!(
+		// strong, the scrolled window must not go away with the page of a closed tab
+		#[weak] editor, #[strong] scrollable_window,
+		move |tab_view| {
+			let Some(page) = tab_view.selected_page() else {
+				return
+			};
+
+			// there is only one editor view, it moves along into the selected tab
+			if let Some(parent) = scrollable_window.parent().and_downcast::<gtk::Box>() {
+				parent.remove(&scrollable_window);
+			}
+			if let Ok(child) = page.child().downcast::<gtk::Box>() {
+				child.append(&scrollable_window);
+			}
+
+			// the editor is busy while it switches tabs itself, otherwise
+			// a tab was clicked and its note has to be opened
+			let Ok(editor) = editor.try_borrow() else {
+				return
+			};
+			let Some(tab) = editor.tab_for_page(&page) else {
+				return
+			};
+			if tab.path != editor.path {
+				Notes::set_current_path(&tab.path);
+				tab_view
+					.activate_action("app.open-note", Some(&tab.path.display().to_string().to_variant()))
+					.expect("The action `open-note` does not exist.");
+			}
+		}
+	));
+
+	tab_view.connect_close_page(glib::clone!(
+		#[weak] editor,
+		#[upgrade_or] glib::Propagation::Proceed,
+		move |tab_view, page| {
+			let Ok(mut editor) = editor.try_borrow_mut() else {
+				tab_view.close_page_finish(page, false);
+				return glib::Propagation::Stop
+			};
+
+			match editor.close_tab(page) {
+				Ok(()) => glib::Propagation::Proceed,
+				Err(e) => {
+					// the tab stays open and shows that saving failed
+					eprintln!("Could not save note, keeping its tab open: {:?}", e);
+					tab_view.close_page_finish(page, false);
+					glib::Propagation::Stop
+				}
+			}
+		}
+	));
+
+	tab_view.connect_page_reordered(glib::clone!(
+		#[weak] editor,
+		move |_, _, _| {
+			if let Ok(editor) = editor.try_borrow() {
+				editor.tabs_reordered();
+			}
+		}
+	));
+}
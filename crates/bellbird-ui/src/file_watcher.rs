@@ -0,0 +1,171 @@
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::mpsc::TryRecvError;
+use std::time::Duration;
+
+use bellbird_core::watcher::{WatchEvent, WatchEventKind, Watcher};
+use glib::MainContext;
+use gtk::prelude::*;
+
+use crate::dialogue::Dialogue;
+use crate::directory_tree::DirectoryTree;
+use crate::editor_view::Editor;
+use crate::notes_list::NotesList;
+
+// how often the main loop looks for changes reported by the watcher thread
+const DISPATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+// keeps the panels in sync with changes made to the notes directory by
+// other programs, e.g. a sync client or another editor
+pub fn watch(
+	app: &adw::Application,
+	root: &Path,
+	directory_tree: &Rc<RefCell<DirectoryTree>>,
+	notes_list: &Rc<RefCell<NotesList>>,
+	editor: &Rc<RefCell<Editor>>,
+) {
+	let receiver = Watcher::start(root);
+
+	glib::timeout_add_local(DISPATCH_INTERVAL, glib::clone!(
+		#[weak] app, #[weak] directory_tree, #[weak] notes_list, #[weak] editor,
+		#[upgrade_or] glib::ControlFlow::Break,
+		move || {
+			let mut events = vec![];
+			loop {
+				match receiver.try_recv() {
+					Ok(batch) => events.extend(batch),
+					Err(TryRecvError::Empty) => break,
+					Err(TryRecvError::Disconnected) => return glib::ControlFlow::Break,
+				}
+			}
+
+			if !events.is_empty() {
+				dispatch(&app, &events, &directory_tree, &notes_list, &editor);
+			}
+			glib::ControlFlow::Continue
+		}
+	));
+}
+
+fn dispatch(
+	app: &adw::Application,
+	events: &[WatchEvent],
+	directory_tree: &Rc<RefCell<DirectoryTree>>,
+	notes_list: &Rc<RefCell<NotesList>>,
+	editor: &Rc<RefCell<Editor>>,
+) {
+	// the panels might be busy updating themselves, in that case
+	// they pick up the changes on their own
+	if events.iter().any(|event| event.is_directory) {
+		if let Ok(mut directory_tree) = directory_tree.try_borrow_mut() {
+			directory_tree.refresh();
+		}
+	}
+
+	let Ok((notes_path, tag)) = notes_list
+		.try_borrow()
+		.map(|notes_list| (notes_list.path.clone(), notes_list.tag.clone())) else {
+		return
+	};
+	// tagged notes can live in any folder
+	let is_listed = |event: &WatchEvent| match tag {
+		Some(_) => !event.is_directory,
+		None => event.path.parent() == Some(notes_path.as_path()),
+	};
+	if events.iter().any(is_listed) {
+		MainContext::default().spawn_local(glib::clone!(
+			#[weak] notes_list,
+			async move {
+				// another task might hold the list across an await by now
+				if let Ok(mut notes_list) = notes_list.try_borrow_mut() {
+					notes_list.refresh().await;
+				}
+			}
+		));
+	}
+
+	let Ok((editor_path, open_paths)) = editor
+		.try_borrow()
+		.map(|editor| (editor.path.clone(), editor.open_paths())) else {
+		return
+	};
+
+	// notes in the other tabs are simply reloaded when they have no
+	// unsaved changes
+	for event in events.iter().filter(|event| event.kind == WatchEventKind::Modified) {
+		if event.path != editor_path && open_paths.contains(&event.path) {
+			editor.borrow().reload_tab(&event.path);
+		}
+	}
+
+	let open_note_modified = events.iter().any(|event| {
+		event.kind == WatchEventKind::Modified && event.path == editor_path
+	});
+	if open_note_modified {
+		open_note_changed(app, editor);
+	}
+}
+
+fn open_note_changed(
+	app: &adw::Application,
+	editor: &Rc<RefCell<Editor>>,
+) {
+	let Ok(disk_content) = std::fs::read_to_string(&editor.borrow().path) else {
+		return
+	};
+	let (content, has_unsaved_changes) = {
+		let editor = editor.borrow();
+		let buffer = editor.view().buffer();
+		let content = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+		(content.to_string(), editor.has_unsaved_changes())
+	};
+
+	// most likely our own save
+	if disk_content == content {
+		return
+	}
+
+	if !has_unsaved_changes {
+		editor.borrow().reload();
+		return
+	}
+
+	let file_stem = editor.borrow().path.file_stem()
+		.map(|stem| stem.to_string_lossy().to_string())
+		.unwrap_or_default();
+
+	let reload = glib::clone!(
+		#[weak] editor,
+		move || editor.borrow().reload()
+	);
+	let merge = glib::clone!(
+		#[weak] app, #[weak] editor, #[strong] file_stem,
+		move || {
+			match editor.borrow().merge_with_disk() {
+				Ok(0) => (),
+				Ok(conflicts) => Dialogue::new(&app).message(
+					"Merge Conflicts",
+					&format!("´{}´ has {} place(s) that were changed both here and on disk.", file_stem, conflicts),
+					"Both versions are kept between <<<<<<< and >>>>>>> lines, remove the one you don't want.",
+				),
+				Err(e) => Dialogue::new(&app).message(
+					"Merge Failed",
+					&format!("´{}´ could not be merged.", file_stem),
+					&e.to_string(),
+				),
+			}
+		}
+	);
+
+	Dialogue::new(app).warning_choice(
+		"Note Changed on Disk",
+		&format!("´{}´ was changed by another program.", file_stem),
+		"Reload it and lose your unsaved changes, or merge both versions?",
+		vec![
+			("Reload", Box::new(reload) as Box<dyn Fn()>),
+			("Merge", Box::new(merge) as Box<dyn Fn()>),
+		],
+		|| {}
+	);
+}
@@ -0,0 +1,221 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use bellbird_core::history::{DiffLine, History, Revision};
+use gtk::prelude::*;
+
+use crate::editor_view::Editor;
+
+const DIFF_ADDED_TAG: &str = "diff-added";
+const DIFF_REMOVED_TAG: &str = "diff-removed";
+
+#[derive(Debug, Clone)]
+pub struct HistoryPanel {
+	editor: Rc<RefCell<Editor>>,
+	revealer: gtk::Revealer,
+	list_box: gtk::ListBox,
+	diff_view: gtk::TextView,
+	restore_button: gtk::Button,
+	revisions: Rc<RefCell<Vec<Revision>>>,
+}
+
+impl HistoryPanel {
+	pub fn new(editor: &Rc<RefCell<Editor>>) -> Self {
+		let list_box = gtk::ListBox::builder()
+			.selection_mode(gtk::SelectionMode::Single)
+			.css_classes(["history-list"])
+			.build();
+
+		let placeholder = gtk::Label::builder()
+			.label("No earlier versions")
+			.margin_top(20)
+			.margin_bottom(20)
+			.build();
+		list_box.set_placeholder(Some(&placeholder));
+
+		let diff_view = gtk::TextView::builder()
+			.editable(false)
+			.cursor_visible(false)
+			.monospace(true)
+			.wrap_mode(gtk::WrapMode::WordChar)
+			.top_margin(5)
+			.left_margin(5)
+			.right_margin(5)
+			.css_classes(["history-diff"])
+			.build();
+
+		let diff_buffer = diff_view.buffer();
+		diff_buffer.create_tag(Some(DIFF_ADDED_TAG), &[("background", &"#DDF4DD")]);
+		diff_buffer.create_tag(Some(DIFF_REMOVED_TAG), &[("background", &"#F9DEDE")]);
+
+		let restore_button = gtk::Button::builder()
+			.label("Restore")
+			.halign(gtk::Align::End)
+			.sensitive(false)
+			.css_classes(["button"])
+			.build();
+
+		let revealer = gtk::Revealer::builder()
+			.transition_type(gtk::RevealerTransitionType::SlideLeft)
+			.reveal_child(false)
+			.build();
+
+		let history_panel = Self {
+			editor: editor.clone(),
+			revealer,
+			list_box,
+			diff_view,
+			restore_button,
+			revisions: Rc::new(RefCell::new(vec![])),
+		};
+		history_panel.build_ui();
+		history_panel.connect_signals();
+		history_panel
+	}
+
+	fn build_ui(&self) {
+		let panel = gtk::Box::builder()
+			.orientation(gtk::Orientation::Vertical)
+			.width_request(320)
+			.spacing(5)
+			.margin_start(5)
+			.margin_end(5)
+			.margin_bottom(5)
+			.css_classes(["history-panel"])
+			.build();
+
+		let label = gtk::Label::builder()
+			.label("History")
+			.halign(gtk::Align::Start)
+			.margin_top(5)
+			.build();
+
+		let revisions_window = gtk::ScrolledWindow::builder()
+			.child(&self.list_box)
+			.hscrollbar_policy(gtk::PolicyType::Never)
+			.min_content_height(160)
+			.build();
+
+		let diff_window = gtk::ScrolledWindow::builder()
+			.child(&self.diff_view)
+			.hscrollbar_policy(gtk::PolicyType::Never)
+			.vexpand(true)
+			.build();
+
+		panel.append(&label);
+		panel.append(&revisions_window);
+		panel.append(&diff_window);
+		panel.append(&self.restore_button);
+		self.revealer.set_child(Some(&panel));
+	}
+
+	fn connect_signals(&self) {
+		let self_clone = self.clone();
+		self.list_box.connect_row_selected(move |_, row| {
+			let revision = row.and_then(|row| {
+				self_clone.revisions.borrow().get(row.index() as usize).cloned()
+			});
+			self_clone.restore_button.set_sensitive(revision.is_some());
+			match revision {
+				Some(revision) => self_clone.show_diff(&revision),
+				None => self_clone.diff_view.buffer().set_text(""),
+			}
+		});
+
+		let self_clone = self.clone();
+		self.restore_button.connect_clicked(move |_| self_clone.restore_selected());
+
+		// a different note was opened in the editor
+		let self_clone = self.clone();
+		let editor_view = self.editor.borrow().view().clone();
+		editor_view.connect_buffer_notify(move |_| {
+			if self_clone.revealer.reveals_child() {
+				let self_clone = self_clone.clone();
+				glib::idle_add_local_once(move || self_clone.populate());
+			}
+		});
+	}
+
+	pub fn widget(&self) -> &gtk::Revealer {
+		&self.revealer
+	}
+
+	pub fn set_revealed(&self, revealed: bool) {
+		if revealed {
+			self.populate();
+		}
+		self.revealer.set_reveal_child(revealed);
+	}
+
+	fn populate(&self) {
+		self.list_box.remove_all();
+		self.diff_view.buffer().set_text("");
+		self.restore_button.set_sensitive(false);
+
+		let Ok(editor) = self.editor.try_borrow() else {
+			return
+		};
+		let revisions = History::revisions(&editor.path);
+		for revision in revisions.iter() {
+			let date = gtk::glib::DateTime::from_unix_local(revision.timestamp)
+				.ok()
+				.and_then(|date| date.format("%d %b %Y, %H:%M:%S").ok())
+				.map(|date| date.to_string())
+				.unwrap_or_default();
+
+			let label = gtk::Label::builder()
+				.label(&date)
+				.halign(gtk::Align::Start)
+				.margin_top(4)
+				.margin_bottom(4)
+				.margin_start(5)
+				.build();
+			self.list_box.append(&label);
+		}
+		*self.revisions.borrow_mut() = revisions;
+	}
+
+	// shows what changed from the revision to what is in the editor right now
+	fn show_diff(&self, revision: &Revision) {
+		let Ok(revision_content) = History::read(revision) else {
+			return
+		};
+		let buffer = self.editor.borrow().view().buffer();
+		let current_content = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+
+		let diff_buffer = self.diff_view.buffer();
+		diff_buffer.set_text("");
+		for line in History::diff(&revision_content, &current_content) {
+			let mut end = diff_buffer.end_iter();
+			match line {
+				DiffLine::Equal(text) => diff_buffer.insert(&mut end, &format!("  {text}\n")),
+				DiffLine::Added(text) => diff_buffer.insert_with_tags_by_name(
+					&mut end,
+					&format!("+ {text}\n"),
+					&[DIFF_ADDED_TAG]
+				),
+				DiffLine::Removed(text) => diff_buffer.insert_with_tags_by_name(
+					&mut end,
+					&format!("- {text}\n"),
+					&[DIFF_REMOVED_TAG]
+				),
+			}
+		}
+	}
+
+	fn restore_selected(&self) {
+		let Some(revision) = self.list_box.selected_row().and_then(|row| {
+			self.revisions.borrow().get(row.index() as usize).cloned()
+		}) else {
+			return
+		};
+
+		let editor = self.editor.borrow();
+		match History::restore(&editor.path, &revision) {
+			Ok(content) => editor.view().buffer().set_text(&content),
+			Err(e) => eprintln!("Could not restore revision: {:?}", e),
+		}
+		drop(editor);
+		self.populate();
+	}
+}
@@ -20,6 +20,16 @@ pub mod contextmenu;
 pub mod menu;
 pub mod dialogue;
 pub mod notes_list_model_item;
+pub mod directory_tree_model_item;
+pub mod search_panel;
+pub mod search_result_row;
+pub mod search_results_model_item;
+pub mod trash_view;
+pub mod history_panel;
+pub mod file_watcher;
+pub mod markdown_preview;
+pub mod backlinks_panel;
+pub mod preferences_window;
 
 fn main() -> gtk::glib::ExitCode {
 	if let Err(e) = setup_gresources() {
@@ -0,0 +1,173 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+
+use bellbird_core::markdown::{Markdown, MarkdownStyle};
+use gtk::prelude::*;
+
+use crate::editor_view::Editor;
+
+// the preview follows the editor with a small delay while typing
+const RENDER_DELAY: Duration = Duration::from_millis(300);
+const HEADING_SCALES: [f64; 6] = [1.8, 1.5, 1.3, 1.15, 1.0, 0.9];
+
+#[derive(Debug, Clone)]
+pub struct MarkdownPreview {
+	editor_view: sourceview5::View,
+	revealer: gtk::Revealer,
+	text_view: gtk::TextView,
+	render_timeout: Rc<Cell<Option<glib::SourceId>>>,
+	changed_handler: Rc<RefCell<Option<(gtk::TextBuffer, glib::SignalHandlerId)>>>,
+}
+
+impl MarkdownPreview {
+	pub fn new(editor: &Rc<RefCell<Editor>>) -> Self {
+		let text_view = gtk::TextView::builder()
+			.editable(false)
+			.cursor_visible(false)
+			.wrap_mode(gtk::WrapMode::WordChar)
+			.top_margin(10)
+			.bottom_margin(10)
+			.left_margin(15)
+			.right_margin(15)
+			.pixels_below_lines(2)
+			.css_classes(["markdown-preview"])
+			.build();
+		create_tags(&text_view.buffer());
+
+		let scrollable_window = gtk::ScrolledWindow::builder()
+			.child(&text_view)
+			.hscrollbar_policy(gtk::PolicyType::Never)
+			.width_request(360)
+			.vexpand(true)
+			.build();
+
+		let revealer = gtk::Revealer::builder()
+			.transition_type(gtk::RevealerTransitionType::SlideLeft)
+			.reveal_child(false)
+			.child(&scrollable_window)
+			.css_classes(["markdown-preview-panel"])
+			.build();
+
+		let preview = Self {
+			editor_view: editor.borrow().view().clone(),
+			revealer,
+			text_view,
+			render_timeout: Rc::new(Cell::new(None)),
+			changed_handler: Rc::new(RefCell::new(None)),
+		};
+
+		// a different note was opened in the editor
+		let preview_clone = preview.clone();
+		preview.editor_view.connect_buffer_notify(move |_| {
+			if preview_clone.revealer.reveals_child() {
+				preview_clone.follow_buffer();
+			}
+		});
+
+		preview
+	}
+
+	pub fn widget(&self) -> &gtk::Revealer {
+		&self.revealer
+	}
+
+	pub fn set_revealed(&self, revealed: bool) {
+		match revealed {
+			true => self.follow_buffer(),
+			false => self.unfollow_buffer(),
+		}
+		self.revealer.set_reveal_child(revealed);
+	}
+
+	// renders the editor's buffer and keeps doing so whenever it changes
+	fn follow_buffer(&self) {
+		self.unfollow_buffer();
+
+		let buffer = self.editor_view.buffer();
+		let preview_clone = self.clone();
+		let handler_id = buffer.connect_changed(move |_| preview_clone.schedule_render());
+		*self.changed_handler.borrow_mut() = Some((buffer, handler_id));
+
+		self.render();
+	}
+
+	fn unfollow_buffer(&self) {
+		if let Some((buffer, handler_id)) = self.changed_handler.borrow_mut().take() {
+			buffer.disconnect(handler_id);
+		}
+		if let Some(timeout_id) = self.render_timeout.take() {
+			timeout_id.remove();
+		}
+	}
+
+	fn schedule_render(&self) {
+		if let Some(timeout_id) = self.render_timeout.take() {
+			timeout_id.remove();
+		}
+
+		let preview_clone = self.clone();
+		let timeout_id = glib::timeout_add_local_once(RENDER_DELAY, move || {
+			preview_clone.render_timeout.set(None);
+			preview_clone.render();
+		});
+		self.render_timeout.set(Some(timeout_id));
+	}
+
+	fn render(&self) {
+		let buffer = self.editor_view.buffer();
+		let content = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+
+		let preview_buffer = self.text_view.buffer();
+		preview_buffer.set_text("");
+		for span in Markdown::render(&content) {
+			let tags: Vec<&str> = span.styles.iter().map(tag_name).collect();
+			let mut end = preview_buffer.end_iter();
+			preview_buffer.insert_with_tags_by_name(&mut end, &span.text, &tags);
+		}
+	}
+}
+
+fn tag_name(style: &MarkdownStyle) -> &'static str {
+	match style {
+		MarkdownStyle::Heading(1) => "h1",
+		MarkdownStyle::Heading(2) => "h2",
+		MarkdownStyle::Heading(3) => "h3",
+		MarkdownStyle::Heading(4) => "h4",
+		MarkdownStyle::Heading(5) => "h5",
+		MarkdownStyle::Heading(_) => "h6",
+		MarkdownStyle::Strong => "strong",
+		MarkdownStyle::Emphasis => "emphasis",
+		MarkdownStyle::Strikethrough => "strikethrough",
+		MarkdownStyle::Code => "code",
+		MarkdownStyle::CodeBlock => "code-block",
+		MarkdownStyle::Link(_) => "link",
+		MarkdownStyle::Quote => "quote",
+	}
+}
+
+fn create_tags(buffer: &gtk::TextBuffer) {
+	for (index, scale) in HEADING_SCALES.iter().enumerate() {
+		buffer.create_tag(
+			Some(&format!("h{}", index + 1)),
+			&[("scale", scale), ("weight", &700i32)]
+		);
+	}
+
+	buffer.create_tag(Some("strong"), &[("weight", &700i32)]);
+	buffer.create_tag(Some("emphasis"), &[("style", &gtk::pango::Style::Italic)]);
+	buffer.create_tag(Some("strikethrough"), &[("strikethrough", &true)]);
+	buffer.create_tag(Some("code"), &[("family", &"monospace"), ("background", &"#F4F3F8")]);
+	buffer.create_tag(
+		Some("code-block"),
+		&[("family", &"monospace"), ("paragraph-background", &"#F4F3F8")]
+	);
+	buffer.create_tag(
+		Some("link"),
+		&[("foreground", &"#6E5BC4"), ("underline", &gtk::pango::Underline::Single)]
+	);
+	buffer.create_tag(
+		Some("quote"),
+		&[("foreground", &"#8A86A0"), ("style", &gtk::pango::Style::Italic), ("left-margin", &30i32)]
+	);
+}
@@ -2,8 +2,9 @@ use std::cell::RefCell;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
-use bellbird_core::config::{Config, ConfigOptions, ConfigSections};
-use bellbird_core::notes::Notes;
+use bellbird_core::config::{Config, ConfigOptions, ConfigSections, Settings, PANEL_WIDTH_RANGE};
+use bellbird_core::notes::{Note, Notes};
+use bellbird_core::sort::{SortBy, SortDirection, SortMode};
 
 use glib::MainContext;
 use gtk::gio;
@@ -13,14 +14,27 @@ use crate::contextmenu::{BbMenuItem, BbMenuSection, ContextMenu};
 use crate::notes_list_model_item::ListModelItem;
 use crate::notes_list_row::NotesListItem;
 
+const SORT_MODES: [(SortBy, SortDirection, &str); 8] = [
+	(SortBy::Name, SortDirection::Ascending, "Name (A–Z)"),
+	(SortBy::Name, SortDirection::Descending, "Name (Z–A)"),
+	(SortBy::Modified, SortDirection::Descending, "Modified (newest)"),
+	(SortBy::Modified, SortDirection::Ascending, "Modified (oldest)"),
+	(SortBy::Created, SortDirection::Descending, "Created (newest)"),
+	(SortBy::Created, SortDirection::Ascending, "Created (oldest)"),
+	(SortBy::Size, SortDirection::Descending, "Size (largest)"),
+	(SortBy::Size, SortDirection::Ascending, "Size (smallest)"),
+];
 
 #[derive(Debug, Clone)]
 pub struct NotesList {
 	pub path: PathBuf,
+	// set while the list shows the notes of a tag instead of a folder
+	pub tag: Option<String>,
 	pub model: (gio::ListStore, gio::ListStore),
 	pub list_view: (gtk::ListView, gtk::ListView),
 	pub current_note: Rc<RefCell<PathBuf>>,
 	pub selected_ctx_path: Rc<RefCell<PathBuf>>,
+	pub sort_dropdown: gtk::DropDown,
 	panel_box: gtk::Box
 }
 
@@ -41,19 +55,24 @@ impl<'a> NotesList {
 		);
 
 		let panel_box = NotesList::panel_box();
+		let sort_dropdown = Self::sort_dropdown();
 
 		Self {
 			path: path.to_path_buf(),
+			tag: None,
 			model: (model, model_pinned),
 			list_view: (list_view, list_view_pinned),
 			current_note: Rc::new(RefCell::new(path.to_path_buf())),
 			selected_ctx_path: Rc::new(RefCell::new(path.to_path_buf())),
+			sort_dropdown,
 			panel_box
 		}
 	}
 
 	pub async fn update_path(&mut self, path: PathBuf) -> Option<bool> {
 		self.path = path.clone();
+		self.tag = None;
+		self.sort_dropdown.set_sensitive(true);
 		let (model, model_pinned) = &self.model;
 		let this = self.clone();
 
@@ -68,6 +87,8 @@ impl<'a> NotesList {
 			first_child.set_visible(true);
 		}
 
+		self.set_sort_selection(Notes::sort_mode(&self.path));
+
 		MainContext::default().spawn_local(glib::clone!(
 			#[weak] model, #[weak] model_pinned, #[strong] this,
 			async move {
@@ -75,11 +96,7 @@ impl<'a> NotesList {
 					model.remove_all();
 					model_pinned.remove_all();
 					notes.iter().for_each(|note| {
-						let path = note.path.clone();
-						let list_item = ListModelItem::new();
-						list_item.set_name(&note.name);
-						list_item.set_path(&path);
-						//list_item.set_is_pinned(note.is_pinned);
+						let list_item = list_model_item(note);
 						if note.is_pinned {
 							model_pinned.append(&list_item);
 						}
@@ -95,6 +112,62 @@ impl<'a> NotesList {
 		Some(true)
 	}
 
+	// lists the notes of every folder that carry the tag, pinned
+	// notes are not split off as they belong to different folders
+	pub async fn show_tag(&mut self, tag: String) {
+		self.tag = Some(tag.clone());
+		self.sort_dropdown.set_sensitive(false);
+		self.panel_box.first_child().unwrap().set_visible(false);
+
+		let (model, model_pinned) = &self.model;
+		let this = self.clone();
+
+		MainContext::default().spawn_local(glib::clone!(
+			#[weak] model, #[weak] model_pinned, #[strong] this,
+			async move {
+				if let Ok(notes) = Notes::list_by_tag(&tag).await {
+					model.remove_all();
+					model_pinned.remove_all();
+					notes.iter().for_each(|note| {
+						model.append(&list_model_item(note));
+					});
+					this.set_selection();
+				}
+			}
+		));
+	}
+
+	fn sort_dropdown() -> gtk::DropDown {
+		let labels: Vec<&str> = SORT_MODES.iter().map(|(_, _, label)| *label).collect();
+		let sort_dropdown = gtk::DropDown::from_strings(&labels);
+		sort_dropdown.set_halign(gtk::Align::End);
+		sort_dropdown.set_hexpand(true);
+		sort_dropdown.set_margin_end(5);
+		sort_dropdown.set_tooltip_text(Some("Sort notes"));
+		sort_dropdown.set_css_classes(&["sort-dropdown", "flat"]);
+
+		sort_dropdown.connect_selected_notify(|dropdown| {
+			let Some((by, direction, _)) = SORT_MODES.get(dropdown.selected() as usize) else {
+				return
+			};
+			let sort_mode = SortMode::new(*by, *direction).to_config_value();
+			let _ = dropdown.activate_action("app.sort-notes", Some(&sort_mode.to_variant()));
+		});
+
+		sort_dropdown
+	}
+
+	fn set_sort_selection(&self, sort_mode: SortMode) {
+		let position = SORT_MODES
+			.iter()
+			.position(|(by, direction, _)| *by == sort_mode.by && *direction == sort_mode.direction)
+			.unwrap_or(0) as u32;
+
+		if self.sort_dropdown.selected() != position {
+			self.sort_dropdown.set_selected(position);
+		}
+	}
+
 	fn create_list_view(
 		factory: gtk::SignalListItemFactory,
 		vexpand: bool,
@@ -146,6 +219,7 @@ impl<'a> NotesList {
 		factory.connect_setup(move |_factory, item| {
 			let item = item.downcast_ref::<gtk::ListItem>().unwrap();
 			let row = NotesListItem::default();
+			row.setup_drag_source();
 			item.set_child(Some(&row));
 		});
 
@@ -159,7 +233,13 @@ impl<'a> NotesList {
 			child.append_tree_item(
 				&model_item.name(),
 				model_item.path().into(),
-				false,
+				model_item.is_pinned(),
+			);
+			child.set_metadata(
+				model_item.modified(),
+				model_item.size(),
+				model_item.word_count(),
+				&model_item.preview(),
 			);
 		});
 
@@ -167,7 +247,12 @@ impl<'a> NotesList {
 	}
 
 	pub async fn refresh(&mut self) {
-		self.update_path(self.path.clone()).await;
+		match self.tag.clone() {
+			Some(tag) => self.show_tag(tag).await,
+			None => {
+				self.update_path(self.path.clone()).await;
+			}
+		}
 	}
 
 	pub fn update_current_note(&self, path: PathBuf) {
@@ -205,9 +290,9 @@ impl<'a> NotesList {
 
 	fn build_context_menu(&self, app: &adw::Application) {
 		let mut sections = vec![];
-		//let mut sec0 = vec![];
-		//sec0.push(BbMenuItem { label: "Open in New Tab", action: "open-note-in-tab" });
-		//sections.push(BbMenuSection { label: None, items: sec0 });
+		let mut sec0 = vec![];
+		sec0.push(BbMenuItem { label: "Open in New Tab", action: "open-note-in-tab" });
+		sections.push(BbMenuSection { label: None, items: sec0 });
 
 		let mut sec1 = vec![];
 		sec1.push(BbMenuItem { label: "Create Note", action: "create-note" });
@@ -263,7 +348,15 @@ impl<'a> NotesList {
 		let mut should_activate_on_note_items = false;
 		let mut path = PathBuf::from("");
 
-		if parent.widget_name() == "NotesListRow" {
+		// rows nest their labels, so look for the row itself first
+		if let Some(row) = parent
+			.ancestor(NotesListItem::static_type())
+			.and_downcast::<NotesListItem>()
+		{
+			should_activate_on_note_items = true;
+			path.push(row.path());
+		}
+		else if parent.widget_name() == "NotesListRow" {
 			should_activate_on_note_items = true;
 			if let Some(label) = parent.last_child().and_downcast::<gtk::Label>() {
 				path.push(label.label());
@@ -299,11 +392,13 @@ impl<'a> NotesList {
 	}
 
 	fn panel_box() -> gtk::Box {
+		let settings = Settings::get();
 		let notes_panel = gtk::Box::builder()
 			.orientation(gtk::Orientation::Vertical)
 			.vexpand(true)
 			.valign(gtk::Align::Fill)
-			.width_request(195)
+			.width_request(*PANEL_WIDTH_RANGE.start())
+			.visible(settings.notes_list_visible)
 			.margin_top(3)
 			.margin_bottom(3)
 			.margin_end(2)
@@ -313,6 +408,14 @@ impl<'a> NotesList {
 		notes_panel
 	}
 
+	pub fn apply_settings(&self, settings: &Settings) {
+		self.panel_box.set_visible(settings.notes_list_visible);
+		// the width is the position of the pane the panel sits in
+		if let Some(paned) = self.panel_box.parent().and_downcast::<gtk::Paned>() {
+			paned.set_position(settings.notes_list_width);
+		}
+	}
+
 	pub fn build_ui(
 		&self,
 		app: &adw::Application,
@@ -322,6 +425,7 @@ impl<'a> NotesList {
 		let notes_view_pinned = create_list_view_wrapper(
 			"Pinned",
 			pinned_view,
+			None,
 			false,
 			glib::clone!(#[weak] pinned_view,
 				move |wrapper, _scrolled_window, _| {
@@ -338,6 +442,7 @@ impl<'a> NotesList {
 		let notes_view = create_list_view_wrapper(
 			"Notes",
 			view,
+			Some(self.sort_dropdown.upcast_ref()),
 			true,
 			|_, _, _| {}
 		);
@@ -349,9 +454,23 @@ impl<'a> NotesList {
 	}
 }
 
+fn list_model_item(note: &Note) -> ListModelItem {
+	let list_item = ListModelItem::new();
+	list_item.set_name(&note.name);
+	list_item.set_path(&note.path);
+	list_item.set_is_pinned(note.is_pinned);
+	list_item.set_created(note.metadata.created.unwrap_or_default());
+	list_item.set_modified(note.metadata.modified.unwrap_or_default());
+	list_item.set_size(note.metadata.file_size);
+	list_item.set_word_count(note.metadata.word_count as u64);
+	list_item.set_preview(&note.metadata.preview);
+	list_item
+}
+
 fn create_list_view_wrapper<F>(
 	label: &str,
 	view: &gtk::ListView,
+	header_suffix: Option<&gtk::Widget>,
 	vexpand: bool,
 	f: F
 ) -> gtk::Box
@@ -380,7 +499,17 @@ where
 		//.valign(gtk::Align::Fill)
 		.build();
 
-	wrapper.append(&notes_panel_label);
+	let header = gtk::Box::builder()
+		.orientation(gtk::Orientation::Horizontal)
+		.css_classes(["panel-header"])
+		.build();
+
+	header.append(&notes_panel_label);
+	if let Some(header_suffix) = header_suffix {
+		header.append(header_suffix);
+	}
+
+	wrapper.append(&header);
 	wrapper.append(&scrollable_window);
 
 	view.model().unwrap().connect_items_changed(glib::clone!(
@@ -16,6 +16,11 @@ use super::*;
 		pub name: RefCell<String>,
 		pub path: RefCell<String>,
 		pub pinned: RefCell<bool>,
+		pub created: RefCell<i64>,
+		pub modified: RefCell<i64>,
+		pub size: RefCell<u64>,
+		pub word_count: RefCell<u64>,
+		pub preview: RefCell<String>,
 	}
 
 	impl Default for ListModelItem {
@@ -24,6 +29,11 @@ use super::*;
 				name: RefCell::new("".to_string()),
 				path: RefCell::new("".to_string()),
 				pinned: RefCell::new(false),
+				created: RefCell::new(0),
+				modified: RefCell::new(0),
+				size: RefCell::new(0),
+				word_count: RefCell::new(0),
+				preview: RefCell::new("".to_string()),
 			}
 		}
 	}
@@ -42,6 +52,11 @@ use super::*;
 					glib::ParamSpecString::builder("name").default_value(None).build(),
 					glib::ParamSpecString::builder("path").default_value(None).build(),
 					glib::ParamSpecBoolean::builder("pinned").default_value(false).build(),
+					glib::ParamSpecInt64::builder("created").default_value(0).build(),
+					glib::ParamSpecInt64::builder("modified").default_value(0).build(),
+					glib::ParamSpecUInt64::builder("size").default_value(0).build(),
+					glib::ParamSpecUInt64::builder("word-count").default_value(0).build(),
+					glib::ParamSpecString::builder("preview").default_value(None).build(),
 				]
 			});
 			PROPERTIES.as_ref()
@@ -52,6 +67,11 @@ use super::*;
 				"name" => self.name.borrow().to_value(),
 				"path" => self.path.borrow().to_value(),
 				"pinned" => self.pinned.borrow().to_value(),
+				"created" => self.created.borrow().to_value(),
+				"modified" => self.modified.borrow().to_value(),
+				"size" => self.size.borrow().to_value(),
+				"word-count" => self.word_count.borrow().to_value(),
+				"preview" => self.preview.borrow().to_value(),
 				_ => unimplemented!(),
 			}
 		}
@@ -75,6 +95,36 @@ use super::*;
 						.get::<bool>()
 						.expect("The value needs to be of type `bool`");
 					self.pinned.set(pinned);
+				},
+				"created" => {
+					let created = value
+						.get::<i64>()
+						.expect("The value needs to be of type `i64`");
+					self.created.set(created);
+				},
+				"modified" => {
+					let modified = value
+						.get::<i64>()
+						.expect("The value needs to be of type `i64`");
+					self.modified.set(modified);
+				},
+				"size" => {
+					let size = value
+						.get::<u64>()
+						.expect("The value needs to be of type `u64`");
+					self.size.set(size);
+				},
+				"word-count" => {
+					let word_count = value
+						.get::<u64>()
+						.expect("The value needs to be of type `u64`");
+					self.word_count.set(word_count);
+				},
+				"preview" => {
+					let preview = value
+						.get::<String>()
+						.expect("The value needs to be of type `String`");
+					self.preview.set(preview);
 				}
 				_ => unimplemented!(),
 			}
@@ -128,4 +178,54 @@ impl ListModelItem {
 	pub(crate) fn set_is_pinned(&self, pinned: bool) {
 		self.set_property("pinned", pinned.to_value());
 	}
+
+	#[allow(unused)]
+	pub(crate) fn created(&self) -> i64 {
+		self.property::<i64>("created")
+	}
+
+	#[allow(unused)]
+	pub(crate) fn set_created(&self, created: i64) {
+		self.set_property("created", created.to_value());
+	}
+
+	#[allow(unused)]
+	pub(crate) fn modified(&self) -> i64 {
+		self.property::<i64>("modified")
+	}
+
+	#[allow(unused)]
+	pub(crate) fn set_modified(&self, modified: i64) {
+		self.set_property("modified", modified.to_value());
+	}
+
+	#[allow(unused)]
+	pub(crate) fn size(&self) -> u64 {
+		self.property::<u64>("size")
+	}
+
+	#[allow(unused)]
+	pub(crate) fn set_size(&self, size: u64) {
+		self.set_property("size", size.to_value());
+	}
+
+	#[allow(unused)]
+	pub(crate) fn word_count(&self) -> u64 {
+		self.property::<u64>("word-count")
+	}
+
+	#[allow(unused)]
+	pub(crate) fn set_word_count(&self, word_count: u64) {
+		self.set_property("word-count", word_count.to_value());
+	}
+
+	#[allow(unused)]
+	pub(crate) fn preview(&self) -> String {
+		self.property::<String>("preview")
+	}
+
+	#[allow(unused)]
+	pub(crate) fn set_preview(&self, preview: &str) {
+		self.set_property("preview", preview.to_value());
+	}
 }
@@ -10,8 +10,14 @@ pub struct NotesListItem {
 	#[template_child]
 	pub icon: TemplateChild<gtk::Image>,
 	#[template_child]
+	pub content: TemplateChild<gtk::Box>,
+	#[template_child]
 	pub name: TemplateChild<gtk::Label>,
 	#[template_child]
+	pub details: TemplateChild<gtk::Label>,
+	#[template_child]
+	pub preview: TemplateChild<gtk::Label>,
+	#[template_child]
 	pub path: TemplateChild<gtk::Label>,
 
 	//pub name: RefCell<Option<String>>,
@@ -23,7 +29,10 @@ impl Default for NotesListItem {
 	fn default() -> Self {
 		Self {
 			icon: TemplateChild::default(),
+			content: TemplateChild::default(),
 			name: TemplateChild::default(),
+			details: TemplateChild::default(),
+			preview: TemplateChild::default(),
 			path: TemplateChild::default(),
 
 			//name: RefCell::new(None),
@@ -1,8 +1,7 @@
 mod imp;
 
 use std::path::{Path, PathBuf};
-use gtk::{glib, subclass::prelude::*};
-//use gtk::prelude::*;
+use gtk::{gdk, glib, prelude::*, subclass::prelude::*};
 
 glib::wrapper! {
 	pub struct NotesListItem(ObjectSubclass<imp::NotesListItem>)
@@ -36,6 +35,50 @@ impl NotesListItem {
 		imp.path.set_text(&path.display().to_string());
 	}
 
+	pub fn set_metadata(
+		&self,
+		modified: i64,
+		size: u64,
+		word_count: u64,
+		preview: &str,
+	) {
+		let imp = self.imp();
+		let mut details = vec![];
+
+		if let Some(date) = glib::DateTime::from_unix_local(modified)
+			.ok()
+			.and_then(|date| date.format("%d %b %Y, %H:%M").ok())
+		{
+			details.push(date.to_string());
+		}
+
+		let words = if word_count == 1 { "word" } else { "words" };
+		details.push(format!("{} {}", word_count, words));
+		details.push(glib::format_size(size).to_string());
+
+		imp.details.set_text(&details.join(" · "));
+		imp.preview.set_text(preview);
+		imp.preview.set_visible(!preview.is_empty());
+	}
+
+	// notes are moved by dropping them onto a folder of the directory tree
+	pub fn setup_drag_source(&self) {
+		let drag_source = gtk::DragSource::builder()
+			.actions(gdk::DragAction::MOVE)
+			.build();
+
+		drag_source.connect_prepare(|drag_source, _x, _y| {
+			let row = drag_source.widget().and_downcast::<NotesListItem>()?;
+			let path = row.path().display().to_string();
+			Some(gdk::ContentProvider::for_value(&path.to_value()))
+		});
+		self.add_controller(drag_source);
+	}
+
+	pub fn path(&self) -> PathBuf {
+		PathBuf::from(self.imp().path.text().as_str())
+	}
+
 	pub fn set_icon(&self) {
 		self.imp().icon.set_resource(Some("/com/bellbird/notes/icons/note.svg"));
 	}
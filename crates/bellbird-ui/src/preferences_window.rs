@@ -0,0 +1,205 @@
+use std::path::PathBuf;
+
+use adw::prelude::*;
+use gtk::glib;
+use bellbird_core::config::{Settings, FONT_SIZE_RANGE, PANEL_WIDTH_RANGE};
+use bellbird_core::notebooks::Notebooks;
+
+#[derive(Debug, Clone)]
+pub struct PreferencesWindow {
+	app: adw::Application,
+	window: adw::PreferencesWindow,
+}
+
+impl PreferencesWindow {
+	pub fn new(app: &adw::Application) -> Self {
+		let window = adw::PreferencesWindow::builder()
+			.application(app)
+			.title("Preferences")
+			.default_width(520)
+			.default_height(560)
+			.destroy_with_parent(true)
+			.search_enabled(false)
+			.build();
+
+		if let Some(active_window) = app.active_window() {
+			window.set_transient_for(Some(&active_window));
+		}
+
+		Self {
+			app: app.clone(),
+			window,
+		}
+	}
+
+	pub fn present(&self) {
+		let settings = Settings::get();
+		let page = adw::PreferencesPage::builder()
+			.title("General")
+			.icon_name("preferences-system-symbolic")
+			.build();
+
+		page.add(&self.editor_group(&settings));
+		page.add(&self.notes_group(&settings));
+		page.add(&self.notebooks_group());
+		page.add(&self.layout_group(&settings));
+
+		self.window.add(&page);
+		self.window.present();
+	}
+
+	fn editor_group(&self, settings: &Settings) -> adw::PreferencesGroup {
+		let group = adw::PreferencesGroup::builder()
+			.title("Editor")
+			.build();
+
+		let font_size_row = spin_row("Font size", &FONT_SIZE_RANGE, 1.0, settings.font_size as f64);
+		let self_clone = self.clone();
+		font_size_row.connect_value_notify(move |row| {
+			self_clone.update(|settings| settings.font_size = row.value() as u32);
+		});
+
+		let breadcrumb_visible_row = adw::SwitchRow::builder()
+			.title("Show breadcrumb")
+			.active(settings.breadcrumb_visible)
+			.build();
+		let self_clone = self.clone();
+		breadcrumb_visible_row.connect_active_notify(move |row| {
+			self_clone.update(|settings| settings.breadcrumb_visible = row.is_active());
+		});
+
+		group.add(&font_size_row);
+		group.add(&breadcrumb_visible_row);
+		group
+	}
+
+	fn notes_group(&self, settings: &Settings) -> adw::PreferencesGroup {
+		let group = adw::PreferencesGroup::builder()
+			.title("Notes")
+			.description("Relative to your home folder unless the path is absolute")
+			.build();
+
+		let notes_directory_row = adw::EntryRow::builder()
+			.title("Notes directory")
+			.show_apply_button(true)
+			.build();
+		notes_directory_row.set_text(&settings.notes_directory.display().to_string());
+		let self_clone = self.clone();
+		notes_directory_row.connect_apply(move |row| {
+			let notes_directory = PathBuf::from(row.text().trim());
+			self_clone.update(|settings| settings.notes_directory = notes_directory);
+		});
+
+		group.add(&notes_directory_row);
+		group
+	}
+
+	// notebooks are added from the sidebar, the active one can't be removed
+	fn notebooks_group(&self) -> adw::PreferencesGroup {
+		let group = adw::PreferencesGroup::builder()
+			.title("Notebooks")
+			.description("Removing a notebook keeps its notes on disk")
+			.build();
+
+		let active = Notebooks::active();
+		for notebook in Notebooks::list() {
+			let row = adw::ActionRow::builder()
+				.title(notebook.name.as_str())
+				.subtitle(notebook.directory.display().to_string())
+				.build();
+
+			let remove_button = gtk::Button::builder()
+				.icon_name("user-trash-symbolic")
+				.tooltip_text("Remove notebook")
+				.valign(gtk::Align::Center)
+				.sensitive(notebook.name != active.name)
+				.css_classes(["flat"])
+				.build();
+			let self_clone = self.clone();
+			remove_button.connect_clicked(glib::clone!(
+				#[weak] group,
+				#[weak] row,
+				move |_| {
+					if let Err(e) = Notebooks::remove(&notebook.name) {
+						eprintln!("Could not remove notebook: {:?}", e);
+						self_clone.window.add_toast(adw::Toast::new(&e.to_string()));
+						return
+					}
+					group.remove(&row);
+					self_clone.app.activate_action("apply-settings", None);
+				}
+			));
+
+			row.add_suffix(&remove_button);
+			group.add(&row);
+		}
+		group
+	}
+
+	fn layout_group(&self, settings: &Settings) -> adw::PreferencesGroup {
+		let group = adw::PreferencesGroup::builder()
+			.title("Layout")
+			.build();
+
+		let sidebar_visible_row = adw::SwitchRow::builder()
+			.title("Show sidebar")
+			.active(settings.sidebar_visible)
+			.build();
+		let self_clone = self.clone();
+		sidebar_visible_row.connect_active_notify(move |row| {
+			self_clone.update(|settings| settings.sidebar_visible = row.is_active());
+		});
+
+		let sidebar_width_row = spin_row("Sidebar width", &PANEL_WIDTH_RANGE, 10.0, settings.sidebar_width as f64);
+		let self_clone = self.clone();
+		sidebar_width_row.connect_value_notify(move |row| {
+			self_clone.update(|settings| settings.sidebar_width = row.value() as i32);
+		});
+
+		let notes_list_visible_row = adw::SwitchRow::builder()
+			.title("Show notes list")
+			.active(settings.notes_list_visible)
+			.build();
+		let self_clone = self.clone();
+		notes_list_visible_row.connect_active_notify(move |row| {
+			self_clone.update(|settings| settings.notes_list_visible = row.is_active());
+		});
+
+		let notes_list_width_row = spin_row("Notes list width", &PANEL_WIDTH_RANGE, 10.0, settings.notes_list_width as f64);
+		let self_clone = self.clone();
+		notes_list_width_row.connect_value_notify(move |row| {
+			self_clone.update(|settings| settings.notes_list_width = row.value() as i32);
+		});
+
+		group.add(&sidebar_visible_row);
+		group.add(&sidebar_width_row);
+		group.add(&notes_list_visible_row);
+		group.add(&notes_list_width_row);
+		group
+	}
+
+	// every change is saved and applied right away
+	fn update<F>(&self, f: F)
+	where
+		F: FnOnce(&mut Settings)
+	{
+		let mut settings = Settings::get();
+		f(&mut settings);
+		if let Err(e) = settings.save() {
+			eprintln!("Could not save settings: {:?}", e);
+			self.window.add_toast(adw::Toast::new(&e.to_string()));
+			return
+		}
+		self.app.activate_action("apply-settings", None);
+	}
+}
+
+fn spin_row<T>(title: &str, range: &std::ops::RangeInclusive<T>, step: f64, value: f64) -> adw::SpinRow
+where
+	T: Copy + Into<f64>
+{
+	let row = adw::SpinRow::with_range((*range.start()).into(), (*range.end()).into(), step);
+	row.set_title(title);
+	row.set_value(value);
+	row
+}
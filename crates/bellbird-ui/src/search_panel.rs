@@ -0,0 +1,206 @@
+use std::cell::{Cell, RefCell};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use bellbird_core::config::{Config, ConfigOptions, ConfigSections};
+use bellbird_core::search::Search;
+
+use glib::MainContext;
+use gtk::gio;
+use gtk::prelude::*;
+
+use crate::search_result_row::SearchResultRow;
+use crate::search_results_model_item::SearchResultItem;
+
+const MAX_RESULTS: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct SearchPanel {
+	pub model: gio::ListStore,
+	pub list_view: gtk::ListView,
+	pub search_entry: gtk::SearchEntry,
+	pub query: Rc<RefCell<String>>,
+}
+
+impl Default for SearchPanel {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl SearchPanel {
+	pub fn new() -> Self {
+		let model = gio::ListStore::new::<SearchResultItem>();
+		let query = Rc::new(RefCell::new(String::new()));
+		// bumped on every keystroke so results of outdated queries get dropped
+		let generation = Rc::new(Cell::new(0u32));
+
+		let selection_model = gtk::MultiSelection::new(Some(model.clone()));
+		let list_view = gtk::ListView::builder()
+			.model(&selection_model)
+			.factory(&Self::create_list_view_factory())
+			.vexpand(true)
+			.valign(gtk::Align::Fill)
+			.margin_top(5)
+			.margin_bottom(5)
+			.single_click_activate(true)
+			.show_separators(true)
+			.build();
+
+		list_view.connect_activate(glib::clone!(
+			#[strong] query,
+			move |list_view, position| {
+				let model = list_view.model().unwrap();
+				let Some(model_item) = model.item(position).and_downcast::<SearchResultItem>() else {
+					return
+				};
+				model.select_item(position, true);
+
+				let path = model_item.path();
+				let directory = PathBuf::from(&path)
+					.parent()
+					.map(|parent| parent.display().to_string())
+					.unwrap_or_default();
+
+				let mut config = Config::new();
+				let _ = config.set_config_value(
+					ConfigSections::General.as_str(),
+					ConfigOptions::CurrentDirectory,
+					directory.clone()
+				);
+				let _ = config.set_config_value(
+					ConfigSections::General.as_str(),
+					ConfigOptions::CurrentNote,
+					path.clone()
+				);
+
+				list_view
+					.activate_action("app.refresh-notes", Some(&directory.to_variant()))
+					.expect("The action `refresh-notes` does not exist.");
+				list_view
+					.activate_action("app.highlight-search", Some(&query.borrow().to_variant()))
+					.expect("The action `highlight-search` does not exist.");
+				list_view
+					.activate_action("app.open-note", Some(&path.to_variant()))
+					.expect("The action `open-note` does not exist.");
+			}
+		));
+
+		let search_entry = gtk::SearchEntry::builder()
+			.placeholder_text("Search notes")
+			.margin_start(10)
+			.margin_end(10)
+			.margin_top(5)
+			.margin_bottom(5)
+			.build();
+
+		search_entry.connect_search_changed(glib::clone!(
+			#[weak] model, #[strong] query, #[strong] generation,
+			move |entry| {
+				let text = entry.text().to_string();
+				*query.borrow_mut() = text.clone();
+				generation.set(generation.get() + 1);
+				let current_generation = generation.get();
+
+				if text.trim().is_empty() {
+					model.remove_all();
+					return
+				}
+
+				MainContext::default().spawn_local(glib::clone!(
+					#[weak] model, #[strong] generation,
+					async move {
+						// the first query builds the whole index,
+						// so keep it off the main thread
+						let results = gio::spawn_blocking(move || {
+							Search::query(&text, MAX_RESULTS)
+						}).await.unwrap_or_default();
+
+						if generation.get() != current_generation {
+							return
+						}
+
+						model.remove_all();
+						results.iter().for_each(|result| {
+							let list_item = SearchResultItem::new();
+							list_item.set_name(&result.name);
+							list_item.set_path(&result.path.display().to_string());
+							list_item.set_snippet(&result.snippets.join("\n"));
+							model.append(&list_item);
+						});
+					}
+				));
+			}
+		));
+
+		Self {
+			model,
+			list_view,
+			search_entry,
+			query,
+		}
+	}
+
+	fn create_list_view_factory() -> gtk::SignalListItemFactory {
+		let factory = gtk::SignalListItemFactory::new();
+		factory.connect_setup(move |_factory, item| {
+			let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+			let row = SearchResultRow::default();
+			item.set_child(Some(&row));
+		});
+
+		factory.connect_bind(move |_factory, item| {
+			let item = item.downcast_ref::<gtk::ListItem>().unwrap();
+			let model_item = item.item().and_downcast::<SearchResultItem>().unwrap();
+			let child = item.child().and_downcast::<SearchResultRow>().unwrap();
+			item.set_selectable(false);
+
+			child.append_result(
+				&model_item.name(),
+				&PathBuf::from(model_item.path()),
+				&model_item.snippet(),
+			);
+		});
+
+		factory
+	}
+
+	fn view(&self) -> &gtk::ListView {
+		&self.list_view
+	}
+}
+
+pub fn build_ui(search_panel: &Rc<RefCell<SearchPanel>>) -> gtk::Box {
+	let panel = gtk::Box::builder()
+		.orientation(gtk::Orientation::Vertical)
+		.vexpand(true)
+		.valign(gtk::Align::Fill)
+		.width_request(220)
+		.margin_top(3)
+		.margin_bottom(3)
+		.margin_end(2)
+		.name("search-panel")
+		.css_classes(["search-panel"])
+		.build();
+
+	let search_panel_label = gtk::Label::builder()
+		.label("Search")
+		.margin_start(10)
+		.margin_end(5)
+		.margin_top(5)
+		.margin_bottom(5)
+		.halign(gtk::Align::Start)
+		.build();
+
+	let scrollable_window = gtk::ScrolledWindow::builder()
+		.child(search_panel.borrow().view())
+		.hscrollbar_policy(gtk::PolicyType::Never)
+		.vexpand(true)
+		.build();
+
+	panel.append(&search_panel_label);
+	panel.append(&search_panel.borrow().search_entry);
+	panel.append(&scrollable_window);
+
+	panel
+}
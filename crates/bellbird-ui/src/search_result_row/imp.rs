@@ -0,0 +1,36 @@
+use gtk::subclass::prelude::*;
+use gtk::glib;
+
+#[derive(Debug, Default, gtk::CompositeTemplate)]
+#[template(resource = "/com/bellbird/notes/ui/search_result_row.ui")]
+pub struct SearchResultRow {
+	#[template_child]
+	pub icon: TemplateChild<gtk::Image>,
+	#[template_child]
+	pub content: TemplateChild<gtk::Box>,
+	#[template_child]
+	pub name: TemplateChild<gtk::Label>,
+	#[template_child]
+	pub snippet: TemplateChild<gtk::Label>,
+	#[template_child]
+	pub path: TemplateChild<gtk::Label>,
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for SearchResultRow {
+	const NAME: &'static str = "SearchResultRow";
+	type Type = super::SearchResultRow;
+	type ParentType = gtk::Box;
+
+	fn class_init(klass: &mut Self::Class) {
+		klass.bind_template();
+	}
+
+	fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+		obj.init_template();
+	}
+}
+
+impl ObjectImpl for SearchResultRow {}
+impl BoxImpl for SearchResultRow {}
+impl WidgetImpl for SearchResultRow {}
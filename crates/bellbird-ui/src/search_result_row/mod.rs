@@ -0,0 +1,37 @@
+pub(crate) mod imp;
+
+use std::path::Path;
+use gtk::{glib, subclass::prelude::*};
+
+glib::wrapper! {
+	pub struct SearchResultRow(ObjectSubclass<imp::SearchResultRow>)
+		@extends gtk::Widget, gtk::Box;
+}
+
+impl Default for SearchResultRow {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl SearchResultRow {
+	pub(crate) fn new() -> Self {
+		glib::Object::new()
+	}
+
+	pub(crate) fn append_result(
+		&self,
+		name: &str,
+		path: &Path,
+		snippet: &str,
+	) {
+		let imp = self.imp();
+
+		imp.icon.set_resource(Some("/com/bellbird/notes/icons/note.svg"));
+
+		imp.name.set_text(name);
+		imp.snippet.set_text(snippet);
+		imp.snippet.set_visible(!snippet.is_empty());
+		imp.path.set_text(&path.display().to_string());
+	}
+}
@@ -0,0 +1,104 @@
+use gtk::{
+	glib,
+	prelude::*,
+	subclass::prelude::*,
+};
+use std::cell::RefCell;
+use once_cell::sync::Lazy;
+
+mod imp {
+	use glib::property::PropertySet;
+
+	use super::*;
+
+	#[derive(Debug, Default)]
+	pub(crate) struct SearchResultItem {
+		pub name: RefCell<String>,
+		pub path: RefCell<String>,
+		pub snippet: RefCell<String>,
+	}
+
+	#[glib::object_subclass]
+	impl ObjectSubclass for SearchResultItem {
+		const NAME: &'static str = "SearchResultItem";
+		type Type = super::SearchResultItem;
+		type ParentType = gtk::Widget;
+	}
+
+	impl ObjectImpl for SearchResultItem {
+		fn properties() -> &'static [glib::ParamSpec] {
+			static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+				vec![
+					glib::ParamSpecString::builder("name").default_value(None).build(),
+					glib::ParamSpecString::builder("path").default_value(None).build(),
+					glib::ParamSpecString::builder("snippet").default_value(None).build(),
+				]
+			});
+			PROPERTIES.as_ref()
+		}
+
+		fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+			match pspec.name() {
+				"name" => self.name.borrow().to_value(),
+				"path" => self.path.borrow().to_value(),
+				"snippet" => self.snippet.borrow().to_value(),
+				_ => unimplemented!(),
+			}
+		}
+
+		fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+			let value = value
+				.get::<String>()
+				.expect("The value needs to be of type `String`");
+
+			match pspec.name() {
+				"name" => self.name.set(value),
+				"path" => self.path.set(value),
+				"snippet" => self.snippet.set(value),
+				_ => unimplemented!(),
+			}
+		}
+	}
+	impl WidgetImpl for SearchResultItem {}
+}
+
+glib::wrapper! {
+	pub(crate) struct SearchResultItem(ObjectSubclass<imp::SearchResultItem>)
+		@extends gtk::Widget;
+}
+
+impl Default for SearchResultItem {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl SearchResultItem {
+	pub(crate) fn new() -> Self {
+		glib::Object::new()
+	}
+
+	pub(crate) fn name(&self) -> String {
+		self.property::<String>("name")
+	}
+
+	pub(crate) fn set_name(&self, name: &str) {
+		self.set_property("name", name.to_value());
+	}
+
+	pub(crate) fn path(&self) -> String {
+		self.property::<String>("path")
+	}
+
+	pub(crate) fn set_path(&self, path: &str) {
+		self.set_property("path", path.to_value());
+	}
+
+	pub(crate) fn snippet(&self) -> String {
+		self.property::<String>("snippet")
+	}
+
+	pub(crate) fn set_snippet(&self, snippet: &str) {
+		self.set_property("snippet", snippet.to_value());
+	}
+}
@@ -0,0 +1,197 @@
+use bellbird_core::directories::Directories;
+use bellbird_core::trash::{Trash, TrashItem};
+use gtk::prelude::*;
+
+use crate::dialogue::Dialogue;
+
+#[derive(Debug, Clone)]
+pub struct TrashView {
+	app: adw::Application,
+	window: gtk::Window,
+	list_box: gtk::ListBox,
+}
+
+impl TrashView {
+	pub fn new(app: &adw::Application) -> Self {
+		let window = gtk::Window::builder()
+			.application(app)
+			.title("Trash")
+			.default_width(480)
+			.default_height(400)
+			.destroy_with_parent(true)
+			.css_classes(["dialogue", "trash-window"])
+			.build();
+
+		if let Some(active_window) = app.active_window() {
+			window.set_transient_for(Some(&active_window));
+		}
+
+		let list_box = gtk::ListBox::builder()
+			.selection_mode(gtk::SelectionMode::None)
+			.css_classes(["trash-list"])
+			.build();
+
+		let placeholder = gtk::Label::builder()
+			.label("Trash is empty")
+			.margin_top(20)
+			.margin_bottom(20)
+			.build();
+		list_box.set_placeholder(Some(&placeholder));
+
+		Self {
+			app: app.clone(),
+			window,
+			list_box,
+		}
+	}
+
+	pub fn present(&self) {
+		let window_box = gtk::Box::builder()
+			.orientation(gtk::Orientation::Vertical)
+			.margin_start(15)
+			.margin_end(15)
+			.margin_top(10)
+			.margin_bottom(10)
+			.spacing(10)
+			.build();
+
+		let scrollable_window = gtk::ScrolledWindow::builder()
+			.child(&self.list_box)
+			.hscrollbar_policy(gtk::PolicyType::Never)
+			.vexpand(true)
+			.build();
+
+		let empty_button = gtk::Button::builder()
+			.label("Empty Trash")
+			.halign(gtk::Align::End)
+			.css_classes(["button", "destructive-action"])
+			.build();
+
+		let self_clone = self.clone();
+		empty_button.connect_clicked(move |_| self_clone.empty_trash());
+
+		window_box.append(&scrollable_window);
+		window_box.append(&empty_button);
+
+		self.populate();
+		self.window.set_child(Some(&window_box));
+		self.window.present();
+	}
+
+	fn populate(&self) {
+		self.list_box.remove_all();
+		for item in Trash::list() {
+			self.list_box.append(&self.row(&item));
+		}
+	}
+
+	fn row(&self, item: &TrashItem) -> gtk::Box {
+		let row = gtk::Box::builder()
+			.orientation(gtk::Orientation::Horizontal)
+			.spacing(8)
+			.margin_top(4)
+			.margin_bottom(4)
+			.build();
+
+		let icon = match item.is_directory {
+			true => "/com/bellbird/notes/icons/folder-closed.svg",
+			false => "/com/bellbird/notes/icons/note.svg",
+		};
+		let icon = gtk::Image::builder()
+			.resource(icon)
+			.pixel_size(16)
+			.valign(gtk::Align::Start)
+			.build();
+
+		let labels = gtk::Box::builder()
+			.orientation(gtk::Orientation::Vertical)
+			.hexpand(true)
+			.build();
+
+		let name = gtk::Label::builder()
+			.label(item.name.trim_end_matches(".note"))
+			.halign(gtk::Align::Start)
+			.ellipsize(gtk::pango::EllipsizeMode::End)
+			.css_classes(["trash-name"])
+			.build();
+
+		let bellbird_root = Directories::bb_root_directory().unwrap_or_default()
+			.display().to_string();
+		let mut details = item.original_path.display().to_string().replace(&bellbird_root, "");
+		if let Some(date) = gtk::glib::DateTime::from_unix_local(item.deletion_date)
+			.ok()
+			.and_then(|date| date.format("%d %b %Y, %H:%M").ok())
+		{
+			details = format!("{details} · deleted {date}");
+		}
+
+		let details = gtk::Label::builder()
+			.label(&details)
+			.halign(gtk::Align::Start)
+			.ellipsize(gtk::pango::EllipsizeMode::Middle)
+			.css_classes(["details"])
+			.build();
+
+		labels.append(&name);
+		labels.append(&details);
+
+		let restore_button = gtk::Button::builder()
+			.label("Restore")
+			.valign(gtk::Align::Center)
+			.css_classes(["button"])
+			.build();
+
+		let delete_button = gtk::Button::builder()
+			.label("Delete")
+			.valign(gtk::Align::Center)
+			.css_classes(["button"])
+			.build();
+
+		let self_clone = self.clone();
+		let item_clone = item.clone();
+		restore_button.connect_clicked(move |_| {
+			let _ = Trash::restore(&item_clone);
+			self_clone.populate();
+			self_clone.refresh_panels();
+		});
+
+		let self_clone = self.clone();
+		let item_clone = item.clone();
+		delete_button.connect_clicked(move |_| {
+			let _ = Trash::delete_permanently(&item_clone);
+			self_clone.populate();
+		});
+
+		row.append(&icon);
+		row.append(&labels);
+		row.append(&restore_button);
+		row.append(&delete_button);
+		row
+	}
+
+	fn empty_trash(&self) {
+		let self_clone = self.clone();
+		let dialogue = Dialogue::new(&self.app);
+		dialogue.warning_yes_no(
+			"Empty Trash",
+			"Do you really want to permanently delete everything in the trash?",
+			"This cannot be undone.",
+			move || {
+				let _ = Trash::empty();
+				self_clone.populate();
+			},
+			|| {}
+		)
+	}
+
+	// restored notes and folders show up in the panels right away
+	fn refresh_panels(&self) {
+		self.app.activate_action("refresh-directories", None);
+		if let Some(path) = Directories::current_directory_path() {
+			self.app.activate_action(
+				"refresh-notes",
+				Some(&path.display().to_string().to_variant())
+			);
+		}
+	}
+}